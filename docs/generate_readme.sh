@@ -13,7 +13,10 @@ function picture() {
 }
 
 pushd schedule >/dev/null
-printf "# Main App\n\n" > README.md
+printf "# App overview\n\n" > README.md
+picture "App overview" docs/schedule/{light,dark}/app_overview.dot.svg >> README.md
+
+printf "# Main App\n\n" >> README.md
 for path in light/schedule*.svg; do
     file=$(basename "$path")
     name=$(echo "$file" | sed 's|schedule_\(.*\).dot.svg|\1|' | tr '_' ' ')
@@ -1,8 +1,8 @@
 use bevy::prelude::*;
 
-fn main() {
+fn main() -> AppExit {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(bevy_mod_debugdump::CommandLineArgs)
-        .run();
+        .run()
 }
@@ -5,7 +5,7 @@ use bevy::{
     prelude::*,
     render::RenderApp,
 };
-use bevy_mod_debugdump::schedule_graph::{settings::Style, Settings};
+use bevy_mod_debugdump::schedule_graph::{overview::AppOverview, settings::Style, Settings};
 use bevy_render::{
     batching::{
         gpu_preprocessing::{BatchedInstanceBuffers, IndirectParametersBuffers},
@@ -27,16 +27,35 @@ fn main() -> Result<(), std::io::Error> {
     let style_light = Style::light();
     let style_dark = Style::dark_github();
 
+    let overview = AppOverview::new(&app);
+    let link = |sub_app: Option<&str>, schedule: &str| match sub_app {
+        None => Some(format!("schedule_{schedule}.dot.svg")),
+        Some("RenderApp") => Some(format!("render_schedule_{schedule}.dot.svg")),
+        Some(_) => None,
+    };
+    for (dir, style) in [("light", &style_light), ("dark", &style_dark)] {
+        let settings = Settings {
+            style: style.clone(),
+            ..Settings::default()
+        };
+        std::fs::write(
+            schedule_path.join(dir).join("app_overview.dot"),
+            overview.to_dot(&settings, link),
+        )?;
+    }
+
     app.world_mut()
         .resource_scope::<Schedules, _>(|world, mut schedules| {
             initialize_schedules(&mut schedules, world)?;
 
             let settings_light = Settings {
                 style: style_light.clone(),
+                show_conditions: true,
                 ..Settings::default()
             };
             let settings_dark = Settings {
                 style: style_dark.clone(),
+                show_conditions: true,
                 ..Settings::default()
             };
             for (label, schedule) in schedules.iter() {
@@ -86,11 +105,13 @@ fn main() -> Result<(), std::io::Error> {
                     ];
                     let settings_light = Settings {
                         style: style_light.clone(),
+                        show_conditions: true,
                         ..Default::default()
                     }
                     .without_single_ambiguities_on_one_of(ignore_ambiguities);
                     let settings_dark = Settings {
                         style: style_dark.clone(),
+                        show_conditions: true,
                         ..Default::default()
                     }
                     .without_single_ambiguities_on_one_of(ignore_ambiguities);
@@ -0,0 +1,10 @@
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy_mod_debugdump::schedule_graph::Settings;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.build().disable::<LogPlugin>());
+    let svg = bevy_mod_debugdump::schedule_graph_svg(&mut app, Update, &Settings::default());
+    println!("{svg}");
+}
@@ -1,15 +1,27 @@
 use std::{fs::File, path::PathBuf};
 
-use bevy_app::App;
+use bevy_app::{App, InternedAppLabel, PluginsState};
 use bevy_ecs::{
     intern::Interned,
-    message::MessageWriter,
+    resource::Resource,
     schedule::{ScheduleLabel, Schedules},
 };
-use bevy_log::{error, info};
+use bevy_log::{error, info, warn};
 use std::io::Write;
 
-use crate::{schedule_graph, schedule_graph_dot};
+use crate::{
+    ambiguity_baseline, check_ambiguity_baseline, schedule_data_flow_graph_dot, schedule_graph,
+    schedule_graph::analysis::ScheduleAnalysis,
+    schedule_graph::config::SettingsConfig,
+    schedule_graph::filter::Filter,
+    schedule_graph::message_flow::MessageFlow,
+    schedule_graph::model::SetKind,
+    schedule_graph::overview::AppOverview,
+    schedule_graph::path::DependencyPaths,
+    schedule_graph::settings::FocusDirection,
+    schedule_graph::settings::{EdgeStyle, RankDir, Style},
+    schedule_graph_dot, schedule_model, schedule_models,
+};
 
 /// Check the command line for arguments relevant to this crate.
 ///
@@ -17,10 +29,83 @@ use crate::{schedule_graph, schedule_graph_dot};
 ///
 /// Use `dump-update-schedule <file.dot>` to dump the `Update` schedule graph.
 ///
+/// Use `dump-all-schedules <dir>` to write every schedule to `<dir>/schedule_<name>.dot`,
+/// along with an `index.md` listing them.
+///
+/// Use `dump-app-overview` to dump a graph of the schedules of the app and its sub-apps in the order they run,
+/// with the number of systems of each. Schedules link to `schedule_<name>.dot.svg`, and those of a sub-app to
+/// `<sub-app>/schedule_<name>.dot.svg`, the files `dot -Tsvg -O` renders from the output of `dump-all-schedules <dir>`
+/// and `--sub-app <sub-app> dump-all-schedules <dir>/<sub-app>`.
+///
+/// ## List schedules, systems and sets
+///
+/// Use `list-schedules` to print all schedules with their number of systems and sets,
+/// and `list-systems <schedule_name>` or `list-sets <schedule_name>` to print their content.
+/// Pass `*` as the schedule name to list all schedules, and `--full-names` to print full type paths.
+/// Every line starts with the schedule name, so the output can be grepped to find where a system lives.
+///
+/// ## Explain the order of two systems
+///
+/// Use `dump-dependency-path <schedule_name> <system> <system>` to dump the schedule graph with every ordering path
+/// between the two systems highlighted, and log the shortest one. Systems are matched by name or full name.
+/// If neither system has to run before the other, they are reported as unordered.
+/// Paths are searched in the whole schedule, so orderings through systems hidden by the filters are found too.
+///
+/// ## Data flow
+///
+/// Use `dump-data-flow <schedule_name>` to dump a graph of the components and resources of a schedule,
+/// with edges from the systems writing them and to the systems reading them.
+///
+/// ## Message flow
+///
+/// Use `dump-message-flow <schedule_name>` to dump a graph with an edge from every system writing a message type to every system reading it,
+/// and log whether each reader sees the messages in the same frame, the next frame, or depending on the executor.
+///
+/// ## Analyse parallelism
+///
+/// Use `dump-critical-path <schedule_name>` to dump the schedule graph with the longest chain of systems highlighted,
+/// and log the width of each topological level and the exclusive systems.
+///
+/// ## Check for new ambiguities
+///
+/// Use `dump-ambiguities <schedule_name> -o ambiguities.txt` to write the ambiguities of a schedule
+/// to a baseline file, and `check-ambiguities <schedule_name> ambiguities.txt` to exit with an error
+/// code when the schedule has ambiguities that are not in the baseline.
+/// The error is returned as [`AppExit::Error`](bevy_app::AppExit::Error) from [`App::run`], so `main` has to return it
+/// for the process to exit with that code, as in the example below.
+///
+/// ## Sub-apps
+///
+/// All commands except `dump-app-overview` look at the schedules of the main app. Use `--sub-app <label>`, e.g. `--sub-app RenderApp`,
+/// to look at the schedules of a sub-app instead.
+/// The `ExtractSchedule` of the render app reads from the main world, so dumping it requires the `render` feature.
+/// Without it, commands naming `ExtractSchedule` fail with an error and commands covering all schedules skip it.
+///
+/// ## Settings
+///
+/// The graph can be configured like [`schedule_graph::Settings`], either with a config file passed with
+/// `--config debugdump.toml` (see [`schedule_graph::config`] for the format), or with the flags
+/// `--style <light|dark_github|dark_discord>`, `--rankdir <LR|TD>`, `--edge-style <spline|ortho|...>`,
+/// `--collapse-single-system-sets`, `--keep-transitive-edges`, `--ambiguities`, `--ambiguities-on-world`,
+/// `--filter-crate <crate>` (can be repeated) and `--filter-name <substring>`.
+/// `--include <glob>` and `--exclude <glob>` match full system names, `--include-set <glob>` and
+/// `--exclude-set <glob>` the names of the sets systems are in. They can be repeated, and a system is
+/// included if it matches any include and no exclude, e.g. `--include 'my_game::*' --exclude 'my_game::debug::*'`.
+///
+/// `--focus <glob>` and `--focus-set <glob>` centre the graph on the matching systems, showing only systems within
+/// `--focus-depth <n>` dependency hops (1 by default) in `--focus-direction <upstream|downstream|both>`.
+/// `dump-schedule Update --focus '*apply_velocity' --focus-depth 3 --focus-direction upstream` shows what must run before `apply_velocity`.
+/// Flags override the values of the config file, except for the `--include`/`--exclude` globs, which are added to the ones of the config.
+/// The filters also apply to the `list-*` commands.
+///
 /// ## Exit the app
 ///
-/// By default the app will exit after performing the dump. If you want to keep
-/// the app running, use `--no-exit`.
+/// By default the app will exit after performing the dump, without running the main loop or opening
+/// a window. If you want to keep the app running, use `--no-exit`.
+///
+/// Plugins added before this one are still built, so `WinitPlugin` still creates its event loop and `RenderPlugin`
+/// still initializes the GPU. To dump on a machine without a display or GPU, disable `WinitPlugin` and set the
+/// backends of the `WgpuSettings` of `RenderPlugin` to `None`.
 ///
 /// # Usage
 ///
@@ -30,65 +115,298 @@ use crate::{schedule_graph, schedule_graph_dot};
 /// ```rust,no_run
 /// use bevy::prelude::*;
 ///
-/// fn main() {
+/// fn main() -> AppExit {
 ///     App::new()
 ///         .add_plugins(DefaultPlugins)
 ///         // Include all other setup as normal.
 ///         .add_plugins(bevy_mod_debugdump::CommandLineArgs)
-///         .run();
+///         .run()
 /// }
 /// ```
 ///
 pub struct CommandLineArgs;
 
+/// The arguments parsed in [`CommandLineArgs::build`], taken when the dump is performed in [`CommandLineArgs::finish`].
+#[derive(Resource)]
+struct ParsedArgs(Result<Args, lexopt::Error>);
+
 impl bevy_app::Plugin for CommandLineArgs {
-    fn build(&self, _app: &mut App) {}
+    fn build(&self, app: &mut App) {
+        let args = parse_args();
+
+        // Plugins added before this one have set up their runner, e.g. the winit event loop.
+        // Replace it when the app is going to exit anyway, so that no window is opened.
+        let exits = match &args {
+            Ok(args) => args.exit && !matches!(args.command, ArgsCommand::None),
+            Err(_) => true,
+        };
+        if exits {
+            app.set_runner(dump_runner);
+        }
+
+        app.insert_resource(ParsedArgs(args));
+    }
 
     fn finish(&self, app: &mut App) {
-        let exit = match execute_cli(app) {
-            Ok(args) => args.exit,
+        let Some(ParsedArgs(args)) = app.world_mut().remove_resource::<ParsedArgs>() else {
+            return;
+        };
+        let result = args
+            .map_err(Into::into)
+            .and_then(|args| execute_cli(app, args));
+        let (exit, app_exit) = match result {
+            Ok(args) => (args.exit, bevy_app::AppExit::Success),
             Err(e) => {
                 error!("{e:?}");
-                true
+                (true, bevy_app::AppExit::error())
             }
         };
 
         if exit {
-            // TODO: It would be nice if we could exit before the window
-            // opens, but I don't see how.
-            app.add_systems(
-                bevy_app::First,
-                |mut app_exit_events: MessageWriter<bevy_app::AppExit>| {
-                    app_exit_events.write(bevy_app::AppExit::Success);
-                },
-            );
+            // Exits after the first update if the runner was replaced by a later plugin.
+            app.world_mut().write_message(app_exit);
         }
     }
 }
 
+/// Finishes building the app, which performs the dump in [`CommandLineArgs::finish`],
+/// and exits without running the main loop.
+fn dump_runner(mut app: App) -> bevy_app::AppExit {
+    while app.plugins_state() == PluginsState::Adding {
+        #[cfg(not(target_arch = "wasm32"))]
+        bevy_tasks::tick_global_task_pools_on_main_thread();
+    }
+    app.finish();
+    app.cleanup();
+
+    app.should_exit().unwrap_or(bevy_app::AppExit::Success)
+}
+
 struct Args {
     command: ArgsCommand,
     exit: bool,
     /// The path to write the graph dot to. If unset, write to stdout.
     out_path: Option<PathBuf>,
+    /// Whether to list systems by their full name.
+    full_names: bool,
+    /// The sub-app whose schedules to use instead of the main app's.
+    sub_app: Option<String>,
+    settings: SettingsArgs,
+}
+
+/// Options that map to [`schedule_graph::Settings`].
+#[derive(Default)]
+struct SettingsArgs {
+    config: Option<SettingsConfig>,
+    style: Option<Style>,
+    rankdir: Option<RankDir>,
+    edge_style: Option<EdgeStyle>,
+    collapse_single_system_sets: bool,
+    keep_transitive_edges: bool,
+    show_access: bool,
+    show_conditions: bool,
+    ambiguities: bool,
+    ambiguities_on_world: bool,
+    filter_crates: Vec<String>,
+    filter_name: Option<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    include_sets: Vec<String>,
+    exclude_sets: Vec<String>,
+    focus: Vec<String>,
+    focus_sets: Vec<String>,
+    focus_depth: Option<usize>,
+    focus_direction: Option<FocusDirection>,
+}
+
+impl SettingsArgs {
+    fn settings(&self) -> schedule_graph::Settings {
+        let mut config = self.config.clone().unwrap_or_default();
+        // `--style` replaces the preset of the config, but the colors set in the config still apply on top of it
+        let mut settings = schedule_graph::Settings::default();
+        if let Some(style) = &self.style {
+            settings.style = style.clone();
+            config.style.preset = None;
+        }
+        // systems matching an include of either the config or the flags are included
+        config.include.extend(self.include.iter().cloned());
+        config
+            .include_sets
+            .extend(self.include_sets.iter().cloned());
+        config.exclude.extend(self.exclude.iter().cloned());
+        config
+            .exclude_sets
+            .extend(self.exclude_sets.iter().cloned());
+        let mut settings = config.apply(settings);
+        if self.collapse_single_system_sets {
+            settings.collapse_single_system_sets = true;
+        }
+        if self.keep_transitive_edges {
+            settings.remove_transitive_edges = false;
+        }
+        if self.show_access {
+            settings.show_access = true;
+        }
+        if self.show_conditions {
+            settings.show_conditions = true;
+        }
+        if self.ambiguities || self.ambiguities_on_world {
+            settings.ambiguity_enable = true;
+        }
+        if self.ambiguities_on_world {
+            settings.ambiguity_enable_on_world = true;
+        }
+        if let Some(rankdir) = self.rankdir {
+            settings.style.schedule_rankdir = rankdir;
+        }
+        if let Some(edge_style) = self.edge_style {
+            settings.style.edge_style = edge_style;
+        }
+
+        let crates = self.filter_crates.clone();
+        let name = self.filter_name.clone();
+        if !crates.is_empty() || name.is_some() {
+            let previous = settings.include_system.take();
+            settings.include_system = Some(Box::new(move |system| {
+                let system_name = system.name().to_string();
+                let in_crate = crates.is_empty()
+                    || crates
+                        .iter()
+                        .any(|crate_| system_name.starts_with(crate_.as_str()));
+                let matches_name = name
+                    .as_ref()
+                    .is_none_or(|name| system_name.contains(name.as_str()));
+                previous.as_ref().is_none_or(|previous| previous(system))
+                    && in_crate
+                    && matches_name
+            }));
+        }
+        if let Some(filter) = Filter::any_of(&self.focus, &self.focus_sets) {
+            settings = settings.focus_on(
+                filter,
+                self.focus_depth.unwrap_or(1),
+                self.focus_direction.unwrap_or_default(),
+            );
+        }
+        if let Some(focus) = &mut settings.focus {
+            if let Some(depth) = self.focus_depth {
+                focus.depth = depth;
+            }
+            if let Some(direction) = self.focus_direction {
+                focus.direction = direction;
+            }
+        }
+
+        settings
+    }
 }
 
 /// A command to execute from the CLI.
 enum ArgsCommand {
     None,
+    /// Prints the usage.
+    Help,
     /// Dumps the schedule graph.
     DumpSchedule {
         /// The schedule to dump.
         schedule: String,
     },
+    /// Dumps every schedule into a directory.
+    DumpAllSchedules {
+        /// The directory to write the graphs and index to.
+        dir: PathBuf,
+    },
+    /// Dumps the schedules of the app and its sub-apps in the order they run.
+    DumpAppOverview,
+    /// Lists all schedules.
+    ListSchedules,
+    /// Lists the systems of a schedule, or of all schedules for `*`.
+    ListSystems {
+        schedule: String,
+    },
+    /// Lists the system sets of a schedule, or of all schedules for `*`.
+    ListSets {
+        schedule: String,
+    },
+    /// Dumps the schedule graph with the ordering paths between two systems highlighted.
+    DumpDependencyPath {
+        schedule: String,
+        first: String,
+        second: String,
+    },
+    /// Dumps the data-flow graph of a schedule.
+    DumpDataFlow {
+        schedule: String,
+    },
+    /// Dumps the message flow between the systems of a schedule.
+    DumpMessageFlow {
+        schedule: String,
+    },
+    /// Dumps the schedule graph with the critical path highlighted.
+    DumpCriticalPath {
+        schedule: String,
+    },
+    /// Dumps the ambiguities of a schedule as a baseline.
+    DumpAmbiguities {
+        schedule: String,
+    },
+    /// Fails if a schedule has ambiguities that are not in the baseline.
+    CheckAmbiguities {
+        schedule: String,
+        /// The baseline written by `dump-ambiguities`.
+        baseline: PathBuf,
+    },
 }
 
+const USAGE: &str = "Usage:\n\
+        dump-schedule <schedule_name> \n\
+        dump-all-schedules <dir> \n\
+        dump-app-overview \n\
+        list-schedules \n\
+        list-systems <schedule_name or *> \n\
+        list-sets <schedule_name or *> \n\
+        dump-dependency-path <schedule_name> <system> <system> \n\
+        dump-data-flow <schedule_name> \n\
+        dump-message-flow <schedule_name> \n\
+        dump-critical-path <schedule_name> \n\
+        dump-ambiguities <schedule_name> \n\
+        check-ambiguities <schedule_name> <baseline_file> \n\n\
+          -o, --output  Write output to file instead of printing to stdout\n\
+          --no-exit     Do not exit after performing debugdump actions\n\
+          --full-names  List systems by their full type name\n\
+          --sub-app <label>  Use the schedules of a sub-app, e.g. RenderApp\n\n\
+        Settings:\n\
+          --config <file.toml>        Load settings from a config file, overridden by the flags below\n\
+          --style <light|dark_github|dark_discord>\n\
+          --rankdir <LR|TD>\n\
+          --edge-style <none|line|polyline|curved|ortho|spline>\n\
+          --collapse-single-system-sets\n\
+          --keep-transitive-edges\n\
+          --show-access               List the components and resources each system reads and writes\n\
+          --show-conditions           Draw the run conditions of systems and sets\n\
+          --ambiguities\n\
+          --ambiguities-on-world\n\
+          --filter-crate <crate>      Only include systems from this crate, can be repeated\n\
+          --filter-name <substring>   Only include systems whose name contains this\n\
+          --include <glob>            Only include systems whose full name matches, can be repeated\n\
+          --exclude <glob>            Exclude systems whose full name matches, can be repeated\n\
+          --include-set <glob>        Only include systems in a matching set, can be repeated\n\
+          --exclude-set <glob>        Exclude systems in a matching set, can be repeated\n\
+          --focus <glob>              Only show systems around the matching systems, can be repeated\n\
+          --focus-set <glob>          Only show systems around the systems of a matching set, can be repeated\n\
+          --focus-depth <n>           Number of dependency hops around the focused systems, 1 by default\n\
+          --focus-direction <upstream|downstream|both>";
+
 fn parse_args() -> Result<Args, lexopt::Error> {
     use lexopt::prelude::*;
 
     let mut command = ArgsCommand::None;
     let mut exit = true;
     let mut out_path = None;
+    let mut full_names = false;
+    let mut sub_app = None;
+    let mut settings = SettingsArgs::default();
+    let mut help = false;
 
     let mut parser = lexopt::Parser::from_env();
     while let Some(arg) = parser.next()? {
@@ -101,37 +419,115 @@ fn parse_args() -> Result<Args, lexopt::Error> {
                 if value == "dump-schedule" {
                     let schedule = parser.value()?.parse()?;
                     command = ArgsCommand::DumpSchedule { schedule };
+                } else if value == "dump-all-schedules" {
+                    let dir = parser.value()?.parse()?;
+                    command = ArgsCommand::DumpAllSchedules { dir };
+                } else if value == "dump-app-overview" {
+                    command = ArgsCommand::DumpAppOverview;
+                } else if value == "list-schedules" {
+                    command = ArgsCommand::ListSchedules;
+                } else if value == "list-systems" {
+                    let schedule = parser.value()?.parse()?;
+                    command = ArgsCommand::ListSystems { schedule };
+                } else if value == "list-sets" {
+                    let schedule = parser.value()?.parse()?;
+                    command = ArgsCommand::ListSets { schedule };
+                } else if value == "dump-dependency-path" {
+                    let schedule = parser.value()?.parse()?;
+                    let first = parser.value()?.parse()?;
+                    let second = parser.value()?.parse()?;
+                    command = ArgsCommand::DumpDependencyPath {
+                        schedule,
+                        first,
+                        second,
+                    };
+                } else if value == "dump-data-flow" {
+                    let schedule = parser.value()?.parse()?;
+                    command = ArgsCommand::DumpDataFlow { schedule };
+                } else if value == "dump-message-flow" {
+                    let schedule = parser.value()?.parse()?;
+                    command = ArgsCommand::DumpMessageFlow { schedule };
+                } else if value == "dump-critical-path" {
+                    let schedule = parser.value()?.parse()?;
+                    command = ArgsCommand::DumpCriticalPath { schedule };
+                } else if value == "dump-ambiguities" {
+                    let schedule = parser.value()?.parse()?;
+                    command = ArgsCommand::DumpAmbiguities { schedule };
+                } else if value == "check-ambiguities" {
+                    let schedule = parser.value()?.parse()?;
+                    let baseline = parser.value()?.parse()?;
+                    command = ArgsCommand::CheckAmbiguities { schedule, baseline };
                 } else {
                     return Err(arg.unexpected());
                 }
             }
             Short('o') | Long("output") => out_path = Some(parser.value()?.parse()?),
             Long("no-exit") => exit = false,
-            Long("help") => {
-                info!(
-                    "Usage:\n\
-                    dump-schedule <schedule_name> \n\n\
-                      -o, --output  Write output to file instead of printing to stdout\n\
-                      --no-exit     Do not exit after performing debugdump actions"
-                );
-                std::process::exit(0);
+            Long("full-names") => full_names = true,
+            Long("sub-app") => sub_app = Some(parser.value()?.parse()?),
+            Long("config") => {
+                settings.config = Some(parser.value()?.parse_with(|path| {
+                    let text = std::fs::read_to_string(path)?;
+                    Ok::<_, Box<dyn std::error::Error + Send + Sync>>(SettingsConfig::parse(&text)?)
+                })?)
             }
+            Long("style") => settings.style = Some(parser.value()?.parse()?),
+            Long("rankdir") => settings.rankdir = Some(parser.value()?.parse()?),
+            Long("edge-style") => settings.edge_style = Some(parser.value()?.parse()?),
+            Long("collapse-single-system-sets") => settings.collapse_single_system_sets = true,
+            Long("keep-transitive-edges") => settings.keep_transitive_edges = true,
+            Long("show-access") => settings.show_access = true,
+            Long("show-conditions") => settings.show_conditions = true,
+            Long("ambiguities") => settings.ambiguities = true,
+            Long("ambiguities-on-world") => settings.ambiguities_on_world = true,
+            Long("filter-crate") => settings.filter_crates.push(parser.value()?.parse()?),
+            Long("filter-name") => settings.filter_name = Some(parser.value()?.parse()?),
+            Long("include") => settings.include.push(parser.value()?.parse()?),
+            Long("exclude") => settings.exclude.push(parser.value()?.parse()?),
+            Long("include-set") => settings.include_sets.push(parser.value()?.parse()?),
+            Long("exclude-set") => settings.exclude_sets.push(parser.value()?.parse()?),
+            Long("focus") => settings.focus.push(parser.value()?.parse()?),
+            Long("focus-set") => settings.focus_sets.push(parser.value()?.parse()?),
+            Long("focus-depth") => settings.focus_depth = Some(parser.value()?.parse()?),
+            Long("focus-direction") => settings.focus_direction = Some(parser.value()?.parse()?),
+            Long("help") => help = true,
             _ => return Err(arg.unexpected()),
         }
     }
 
+    if help {
+        command = ArgsCommand::Help;
+    }
+
     Ok(Args {
         command,
         exit,
         out_path,
+        full_names,
+        sub_app,
+        settings,
     })
 }
 
 type Result<T, E = Box<dyn std::error::Error>> = std::result::Result<T, E>;
 
-fn execute_cli(app: &mut App) -> Result<Args> {
-    let mut args = parse_args()?;
+fn execute_cli(app: &mut App, args: Args) -> Result<Args> {
+    match &args.sub_app {
+        // the overview always covers the main app and all sub-apps
+        Some(sub_app)
+            if !matches!(
+                args.command,
+                ArgsCommand::DumpAppOverview | ArgsCommand::Help
+            ) =>
+        {
+            let label = find_sub_app(app, sub_app)?;
+            with_sub_app_world(app, label, |app| execute_command(app, args))
+        }
+        _ => execute_command(app, args),
+    }
+}
 
+fn execute_command(app: &mut App, mut args: Args) -> Result<Args> {
     let write = |out: &str| -> Result<()> {
         match &args.out_path {
             None => {
@@ -152,22 +548,326 @@ fn execute_cli(app: &mut App) -> Result<Args> {
             args.exit = false;
             Ok(args)
         }
+        ArgsCommand::Help => {
+            info!("{USAGE}");
+            Ok(args)
+        }
         ArgsCommand::DumpSchedule { schedule } => {
             let schedule = find_schedule(app, schedule)?;
 
-            let settings = schedule_graph::Settings::default();
+            let settings = args.settings.settings();
             write(&schedule_graph_dot(app, schedule, &settings))?;
 
             Ok(args)
         }
+        ArgsCommand::DumpAllSchedules { dir } => {
+            std::fs::create_dir_all(dir)?;
+
+            let settings = args.settings.settings();
+            let mut index = String::from("# Schedules\n\n");
+            for (name, label) in schedule_labels(app) {
+                let filename = format!("schedule_{}.dot", sanitize_filename(&name));
+                std::fs::write(
+                    dir.join(&filename),
+                    schedule_graph_dot(app, label, &settings),
+                )?;
+                index.push_str(&format!("- [{name}]({filename})\n"));
+            }
+            std::fs::write(dir.join("index.md"), index)?;
+            info!("Wrote schedules to {}", dir.display());
+
+            Ok(args)
+        }
+        ArgsCommand::DumpAppOverview => {
+            let settings = args.settings.settings();
+            let overview = AppOverview::new(app);
+            info!("{}", overview.to_text());
+            write(&overview.to_dot(&settings, |sub_app, schedule| {
+                let filename = format!("schedule_{}.dot.svg", sanitize_filename(schedule));
+                Some(match sub_app {
+                    None => filename,
+                    Some(sub_app) => format!("{}/{filename}", sanitize_filename(sub_app)),
+                })
+            }))?;
+
+            Ok(args)
+        }
+        ArgsCommand::ListSchedules => {
+            let settings = args.settings.settings();
+            let labels = schedule_labels(app);
+            let width = labels.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+            let mut out = String::new();
+            for (name, label) in &labels {
+                let model = schedule_model(app, *label, &settings);
+                let sets = model
+                    .sets
+                    .iter()
+                    .filter(|set| set.kind == SetKind::Regular)
+                    .count();
+                out.push_str(&format!(
+                    "{name:width$}  {:>4} systems  {sets:>4} sets\n",
+                    model.systems.len()
+                ));
+            }
+            out.push_str(&format!("{} schedules", labels.len()));
+            write(&out)?;
+
+            Ok(args)
+        }
+        ArgsCommand::ListSystems { schedule } | ArgsCommand::ListSets { schedule } => {
+            let labels = if schedule == "*" {
+                schedule_labels(app)
+            } else {
+                let label = find_schedule(app, schedule)?;
+                vec![(format!("{label:?}"), label)]
+            };
+            let list_sets = matches!(args.command, ArgsCommand::ListSets { .. });
+
+            let settings = args.settings.settings();
+            let mut out = String::new();
+            let mut count = 0;
+            for (name, label) in labels {
+                let model = schedule_model(app, label, &settings);
+                let names: Vec<&str> = if list_sets {
+                    model
+                        .sets
+                        .iter()
+                        .filter(|set| set.kind == SetKind::Regular)
+                        .map(|set| set.name.as_str())
+                        .collect()
+                } else {
+                    model
+                        .systems
+                        .iter()
+                        .map(|system| {
+                            if args.full_names {
+                                system.full_name.as_str()
+                            } else {
+                                system.name.as_str()
+                            }
+                        })
+                        .collect()
+                };
+                for node_name in &names {
+                    out.push_str(&format!("{name}  {node_name}\n"));
+                }
+                count += names.len();
+            }
+            let kind = if list_sets { "sets" } else { "systems" };
+            out.push_str(&format!("{count} {kind}"));
+            write(&out)?;
+
+            Ok(args)
+        }
+        ArgsCommand::DumpDependencyPath {
+            schedule,
+            first,
+            second,
+        } => {
+            let schedule = find_schedule(app, schedule)?;
+
+            let settings = args.settings.settings();
+            let (model, unfiltered) = schedule_models(app, schedule, &settings);
+            let paths = DependencyPaths::between(&unfiltered, first, second)?;
+            info!("{}", paths.to_text());
+            write(&schedule_graph::schedule_model_dot_highlighted(
+                &model,
+                &settings,
+                &paths.highlight(&settings),
+            ))?;
+
+            Ok(args)
+        }
+        ArgsCommand::DumpDataFlow { schedule } => {
+            let schedule = find_schedule(app, schedule)?;
+
+            let settings = args.settings.settings();
+            write(&schedule_data_flow_graph_dot(app, schedule, &settings))?;
+
+            Ok(args)
+        }
+        ArgsCommand::DumpMessageFlow { schedule } => {
+            let schedule = find_schedule(app, schedule)?;
+
+            let settings = args.settings.settings();
+            let (model, unfiltered) = schedule_models(app, schedule, &settings);
+            let flow = MessageFlow::new(&model, &unfiltered);
+            info!("{}", flow.to_text());
+            write(&flow.to_dot(&settings))?;
+
+            Ok(args)
+        }
+        ArgsCommand::DumpCriticalPath { schedule } => {
+            let schedule = find_schedule(app, schedule)?;
+
+            let settings = args.settings.settings();
+            let (model, unfiltered) = schedule_models(app, schedule, &settings);
+            let analysis = ScheduleAnalysis::new(&unfiltered);
+            info!("{}", analysis.to_text());
+            write(&schedule_graph::schedule_model_dot_highlighted(
+                &model,
+                &settings,
+                &analysis.highlight(&settings),
+            ))?;
+
+            Ok(args)
+        }
+        ArgsCommand::DumpAmbiguities { schedule } => {
+            let schedule = find_schedule(app, schedule)?;
+
+            let settings = args.settings.settings();
+            let baseline = ambiguity_baseline(app, schedule, &settings);
+            write(&baseline.to_text(&format!("{schedule:?}")))?;
+
+            Ok(args)
+        }
+        ArgsCommand::CheckAmbiguities { schedule, baseline } => {
+            let schedule = find_schedule(app, schedule)?;
+
+            let settings = args.settings.settings();
+            let check = check_ambiguity_baseline(app, schedule, baseline, &settings)?;
+            info!("No new ambiguities in {schedule:?}\n{check}");
+
+            Ok(args)
+        }
+    }
+}
+
+/// Looks up a sub-app by the `Debug` name of its label, ignoring case.
+fn find_sub_app(app: &App, sub_app_name: &str) -> Result<InternedAppLabel> {
+    let lower_sub_app_name = sub_app_name.to_lowercase();
+
+    let mut sub_apps: Vec<_> = app
+        .sub_apps()
+        .sub_apps
+        .keys()
+        .map(|label| (format!("{label:?}"), *label))
+        .collect();
+    sub_apps.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    sub_apps
+        .iter()
+        .find(|(name, _)| name.to_lowercase() == lower_sub_app_name)
+        .map(|(_, label)| *label)
+        .ok_or_else(|| {
+            let names: Vec<_> = sub_apps.into_iter().map(|(name, _)| name).collect();
+            format!(
+                "No sub-app matched the requested sub-app '{sub_app_name}'. The valid sub-apps are:\n\n{}",
+                names.join("\n")
+            )
+            .into()
+        })
+}
+
+/// Temporarily swaps the world of the sub-app into the main app, so that `f` can use the
+/// same functions as for the main app.
+///
+/// With the `render` feature the main world is inserted into the sub-app world as
+/// [`MainWorld`](bevy_render::MainWorld), like during extraction, so the systems of
+/// `ExtractSchedule` can be initialized.
+fn with_sub_app_world<T>(
+    app: &mut App,
+    label: InternedAppLabel,
+    f: impl FnOnce(&mut App) -> T,
+) -> T {
+    let sub_app_world = std::mem::take(app.sub_app_mut(label).world_mut());
+    let main_world = std::mem::replace(app.world_mut(), sub_app_world);
+
+    // the extract systems read the main world from the `MainWorld` resource, the guard takes it back from there
+    #[cfg(feature = "render")]
+    let main_world = {
+        let mut inserted = bevy_render::MainWorld::default();
+        *inserted = main_world;
+        app.world_mut().insert_resource(inserted);
+        None
+    };
+    #[cfg(not(feature = "render"))]
+    let main_world = Some(main_world);
+
+    // swaps the worlds back even if `f` panics, e.g. when `--no-exit` keeps the app running
+    let guard = SubAppWorldGuard {
+        app,
+        label,
+        main_world,
+    };
+    f(guard.app)
+}
+
+/// Moves the main world back into the app and the sub-app world back into its sub-app when dropped
+struct SubAppWorldGuard<'a> {
+    app: &'a mut App,
+    label: InternedAppLabel,
+    /// The main world, or `None` while it is in the `MainWorld` resource of the sub-app world
+    main_world: Option<bevy_ecs::world::World>,
+}
+
+impl Drop for SubAppWorldGuard<'_> {
+    fn drop(&mut self) {
+        #[cfg(feature = "render")]
+        if self.main_world.is_none() {
+            let mut inserted = self
+                .app
+                .world_mut()
+                .remove_resource::<bevy_render::MainWorld>()
+                .expect("the `MainWorld` resource was removed from the sub-app world, so the main world can't be restored");
+            self.main_world = Some(std::mem::take(&mut *inserted));
+        }
+        let main_world = self
+            .main_world
+            .take()
+            .expect("the main world is restored only once");
+
+        let sub_app_world = std::mem::replace(self.app.world_mut(), main_world);
+        *self.app.sub_app_mut(self.label).world_mut() = sub_app_world;
     }
 }
 
+/// Whether the systems of the schedule can't be initialized because they read from a `MainWorld` that isn't available.
+///
+/// Without the `render` feature, the `Extract` parameters of the systems in `ExtractSchedule` would panic.
+fn requires_main_world(name: &str) -> bool {
+    !cfg!(feature = "render") && name == "ExtractSchedule"
+}
+
+/// All schedules of the app with their `Debug` name, sorted by name.
+///
+/// Schedules that can't be initialized without the `render` feature are skipped with a warning.
+fn schedule_labels(app: &App) -> Vec<(String, Interned<dyn ScheduleLabel>)> {
+    let mut labels: Vec<_> = app
+        .world()
+        .resource::<Schedules>()
+        .iter()
+        .map(|(_, schedule)| (format!("{:?}", schedule.label()), schedule.label()))
+        .filter(|(name, _)| {
+            let skip = requires_main_world(name);
+            if skip {
+                warn!("Skipping {name}, which requires the `render` feature");
+            }
+            !skip
+        })
+        .collect();
+    labels.sort_by(|(a, _), (b, _)| a.cmp(b));
+    labels
+}
+
+/// Replaces characters of a label's `Debug` name that aren't safe in file names
+fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '(' | ')' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
 enum FindScheduleError {
     /// There was no match. Holds the requested schedule, and the list of valid
     /// schedules by string.
     NoMatch(String, Vec<String>),
     MoreThanOneMatch(String),
+    /// The schedule reads from the main world, which is only available with the `render` feature.
+    RequiresMainWorld(String),
 }
 
 impl std::fmt::Debug for FindScheduleError {
@@ -183,6 +883,9 @@ impl std::fmt::Debug for FindScheduleError {
             Self::MoreThanOneMatch(request) => f.write_fmt(format_args!(
                 "More than one schedule matched requested schedule '{request}'"
             )),
+            Self::RequiresMainWorld(schedule) => f.write_fmt(format_args!(
+                "The systems of '{schedule}' read from the main world, enable the `render` feature to dump it"
+            )),
         }
     }
 }
@@ -223,6 +926,10 @@ fn find_schedule(
     }
 
     if let Some(label) = found_label {
+        let name = format!("{label:?}");
+        if requires_main_world(&name) {
+            return Err(FindScheduleError::RequiresMainWorld(name));
+        }
         Ok(label)
     } else {
         Err(FindScheduleError::NoMatch(
@@ -0,0 +1,135 @@
+use std::fmt::Write;
+
+/// Minimal JSON value, only used for writing.
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// Keys are written in insertion order
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Self {
+        Json::Bool(value)
+    }
+}
+impl From<usize> for Json {
+    fn from(value: usize) -> Self {
+        Json::Number(value as f64)
+    }
+}
+impl From<f64> for Json {
+    fn from(value: f64) -> Self {
+        Json::Number(value)
+    }
+}
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_owned())
+    }
+}
+impl From<String> for Json {
+    fn from(value: String) -> Self {
+        Json::String(value)
+    }
+}
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+impl<T: Into<Json>> FromIterator<T> for Json {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Json::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+#[test]
+fn escape_correctly() {
+    assert_eq!(escape_string("a"), "\"a\"");
+    assert_eq!(escape_string("a \"b\" \\c"), r#""a \"b\" \\c""#);
+    assert_eq!(escape_string("line\nbreak\t"), r#""line\nbreak\t""#);
+    assert_eq!(escape_string("\u{1}"), r#""\u0001""#);
+    assert_eq!(escape_string("Events<A> → b"), "\"Events<A> → b\"");
+}
+
+fn escape_string(input: &str) -> String {
+    let mut out = String::with_capacity(input.len() + 2);
+    out.push('"');
+    for c in input.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Json {
+    /// Formats the value with two spaces of indentation
+    pub fn to_string_pretty(&self) -> String {
+        let mut buffer = String::new();
+        self.write(&mut buffer, 0);
+        buffer
+    }
+
+    fn write(&self, buffer: &mut String, indent: usize) {
+        match self {
+            Json::Null => buffer.push_str("null"),
+            Json::Bool(value) => {
+                let _ = write!(buffer, "{value}");
+            }
+            Json::Number(value) if value.is_finite() => {
+                let _ = write!(buffer, "{value}");
+            }
+            Json::Number(_) => buffer.push_str("null"),
+            Json::String(value) => buffer.push_str(&escape_string(value)),
+            Json::Array(items) if items.is_empty() => buffer.push_str("[]"),
+            Json::Array(items) => {
+                buffer.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        buffer.push(',');
+                    }
+                    newline(buffer, indent + 1);
+                    item.write(buffer, indent + 1);
+                }
+                newline(buffer, indent);
+                buffer.push(']');
+            }
+            Json::Object(fields) if fields.is_empty() => buffer.push_str("{}"),
+            Json::Object(fields) => {
+                buffer.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        buffer.push(',');
+                    }
+                    newline(buffer, indent + 1);
+                    buffer.push_str(&escape_string(key));
+                    buffer.push_str(": ");
+                    value.write(buffer, indent + 1);
+                }
+                newline(buffer, indent);
+                buffer.push('}');
+            }
+        }
+    }
+}
+
+fn newline(buffer: &mut String, indent: usize) {
+    buffer.push('\n');
+    for _ in 0..indent {
+        buffer.push_str("  ");
+    }
+}
@@ -0,0 +1,405 @@
+//! Layered layout of a graph with nested clusters, used to render without graphviz.
+//!
+//! Every cluster is laid out on its own: its direct children (nodes and nested clusters) are assigned
+//! ranks by the longest path through the edges between them, and the cluster becomes a box in its parent.
+//! Edges between items in different clusters are lifted to the children of their common cluster.
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn center(&self) -> (f32, f32) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayoutDirection {
+    LeftRight,
+    TopDown,
+}
+
+pub struct LayoutSpacing {
+    /// Space between two items of the same rank
+    pub node_sep: f32,
+    /// Space between two ranks
+    pub rank_sep: f32,
+    /// Space between a cluster border and its content
+    pub cluster_padding: f32,
+    /// Space around the whole graph
+    pub margin: f32,
+}
+
+impl Default for LayoutSpacing {
+    fn default() -> Self {
+        LayoutSpacing {
+            node_sep: 12.0,
+            rank_sep: 40.0,
+            cluster_padding: 12.0,
+            margin: 8.0,
+        }
+    }
+}
+
+struct Item {
+    parent: Option<usize>,
+    is_cluster: bool,
+    /// Size of the node, or size of the label for clusters
+    width: f32,
+    height: f32,
+}
+
+#[derive(Default)]
+pub struct LayoutGraph {
+    items: Vec<Item>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl LayoutGraph {
+    pub fn add_node(&mut self, parent: Option<usize>, width: f32, height: f32) -> usize {
+        self.add_item(parent, false, width, height)
+    }
+
+    /// Add a cluster whose label of the given size is placed at its top
+    pub fn add_cluster(
+        &mut self,
+        parent: Option<usize>,
+        label_width: f32,
+        label_height: f32,
+    ) -> usize {
+        self.add_item(parent, true, label_width, label_height)
+    }
+
+    fn add_item(
+        &mut self,
+        parent: Option<usize>,
+        is_cluster: bool,
+        width: f32,
+        height: f32,
+    ) -> usize {
+        if let Some(parent) = parent {
+            assert!(self.items[parent].is_cluster);
+        }
+        self.items.push(Item {
+            parent,
+            is_cluster,
+            width,
+            height,
+        });
+        self.items.len() - 1
+    }
+
+    /// Add an edge that `to` should be placed after `from`. Both can be nodes or clusters.
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.edges.push((from, to));
+    }
+
+    /// Returns the rectangle of every node and cluster, in the order they were added
+    pub fn layout(&self, direction: LayoutDirection, spacing: &LayoutSpacing) -> Vec<Rect> {
+        let n = self.items.len();
+
+        // container index `n` is the root
+        let mut children = vec![Vec::new(); n + 1];
+        for (i, item) in self.items.iter().enumerate() {
+            children[item.parent.unwrap_or(n)].push(i);
+        }
+        let mut container_edges = vec![Vec::new(); n + 1];
+        for &(from, to) in &self.edges {
+            if let Some((container, from, to)) = self.lift(from, to) {
+                container_edges[container.unwrap_or(n)].push((from, to));
+            }
+        }
+
+        let mut sizes = vec![(0.0, 0.0); n];
+        let mut offsets = vec![(0.0, 0.0); n];
+        self.arrange(
+            n,
+            &children,
+            &container_edges,
+            direction,
+            spacing,
+            &mut sizes,
+            &mut offsets,
+        );
+
+        let mut rects = vec![Rect::default(); n];
+        self.place(
+            n,
+            (spacing.margin, spacing.margin),
+            &children,
+            &sizes,
+            &offsets,
+            &mut rects,
+        );
+        rects
+    }
+
+    /// Lays out the children of `container` relative to its content origin and returns the content size.
+    /// Nested clusters are arranged first, so that their sizes are known.
+    #[expect(clippy::too_many_arguments)]
+    fn arrange(
+        &self,
+        container: usize,
+        children: &[Vec<usize>],
+        container_edges: &[Vec<(usize, usize)>],
+        direction: LayoutDirection,
+        spacing: &LayoutSpacing,
+        sizes: &mut [(f32, f32)],
+        offsets: &mut [(f32, f32)],
+    ) -> (f32, f32) {
+        let items = &children[container];
+
+        for &item in items {
+            let Item {
+                is_cluster,
+                width,
+                height,
+                ..
+            } = self.items[item];
+            sizes[item] = if is_cluster {
+                let (content_width, content_height) = self.arrange(
+                    item,
+                    children,
+                    container_edges,
+                    direction,
+                    spacing,
+                    sizes,
+                    offsets,
+                );
+                for &child in &children[item] {
+                    offsets[child].0 += spacing.cluster_padding;
+                    offsets[child].1 += spacing.cluster_padding + height;
+                }
+                (
+                    content_width.max(width) + 2.0 * spacing.cluster_padding,
+                    content_height + height + 2.0 * spacing.cluster_padding,
+                )
+            } else {
+                (width, height)
+            };
+        }
+
+        let ranks = rank_items(items, &container_edges[container]);
+
+        // (main, cross) extents depending on direction
+        let extent = |item: usize| match direction {
+            LayoutDirection::LeftRight => sizes[item],
+            LayoutDirection::TopDown => (sizes[item].1, sizes[item].0),
+        };
+
+        let rank_cross_len = |rank: &[usize]| {
+            let len: f32 = rank.iter().map(|&item| extent(item).1).sum();
+            len + spacing.node_sep * rank.len().saturating_sub(1) as f32
+        };
+        let total_cross = ranks
+            .iter()
+            .map(|rank| rank_cross_len(rank))
+            .fold(0.0, f32::max);
+
+        let mut main = 0.0;
+        for rank in &ranks {
+            let rank_main = rank.iter().map(|&item| extent(item).0).fold(0.0, f32::max);
+            let mut cross = (total_cross - rank_cross_len(rank)) / 2.0;
+            for &item in rank {
+                let (item_main, item_cross) = extent(item);
+                let main_pos = main + (rank_main - item_main) / 2.0;
+                offsets[item] = match direction {
+                    LayoutDirection::LeftRight => (main_pos, cross),
+                    LayoutDirection::TopDown => (cross, main_pos),
+                };
+                cross += item_cross + spacing.node_sep;
+            }
+            main += rank_main + spacing.rank_sep;
+        }
+        let total_main = (main - spacing.rank_sep).max(0.0);
+
+        match direction {
+            LayoutDirection::LeftRight => (total_main, total_cross),
+            LayoutDirection::TopDown => (total_cross, total_main),
+        }
+    }
+
+    fn place(
+        &self,
+        container: usize,
+        origin: (f32, f32),
+        children: &[Vec<usize>],
+        sizes: &[(f32, f32)],
+        offsets: &[(f32, f32)],
+        rects: &mut [Rect],
+    ) {
+        for &item in &children[container] {
+            let (x, y) = (origin.0 + offsets[item].0, origin.1 + offsets[item].1);
+            rects[item] = Rect {
+                x,
+                y,
+                width: sizes[item].0,
+                height: sizes[item].1,
+            };
+            self.place(item, (x, y), children, sizes, offsets, rects);
+        }
+    }
+
+    /// Finds the common container of `a` and `b`, and the children of that container containing them.
+    /// Returns `None` if one contains the other.
+    fn lift(&self, a: usize, b: usize) -> Option<(Option<usize>, usize, usize)> {
+        let depth = |mut item: usize| {
+            let mut depth = 0;
+            while let Some(parent) = self.items[item].parent {
+                item = parent;
+                depth += 1;
+            }
+            depth
+        };
+        let (mut a, mut b) = (a, b);
+        let (mut depth_a, mut depth_b) = (depth(a), depth(b));
+        while depth_a > depth_b {
+            a = self.items[a].parent?;
+            depth_a -= 1;
+            if a == b {
+                return None;
+            }
+        }
+        while depth_b > depth_a {
+            b = self.items[b].parent?;
+            depth_b -= 1;
+            if a == b {
+                return None;
+            }
+        }
+        while self.items[a].parent != self.items[b].parent {
+            a = self.items[a].parent?;
+            b = self.items[b].parent?;
+        }
+        (a != b).then_some((self.items[a].parent, a, b))
+    }
+}
+
+/// Assigns each item to a rank by the longest path leading to it, ignoring edges that would form a cycle.
+/// Items of a rank are ordered by the average position of their predecessors.
+fn rank_items(items: &[usize], edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let local = |item: usize| items.iter().position(|&i| i == item);
+    let mut successors = vec![Vec::new(); items.len()];
+    for &(from, to) in edges {
+        if let (Some(from), Some(to)) = (local(from), local(to)) {
+            if from != to && !successors[from].contains(&to) {
+                successors[from].push(to);
+            }
+        }
+    }
+
+    // drop back edges found by a depth first search
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Active,
+        Done,
+    }
+    let mut visit = vec![Visit::New; items.len()];
+    let mut order = Vec::with_capacity(items.len());
+    for start in 0..items.len() {
+        if visit[start] != Visit::New {
+            continue;
+        }
+        let mut stack = vec![(start, 0)];
+        visit[start] = Visit::Active;
+        while let Some(&mut (node, ref mut next)) = stack.last_mut() {
+            if let Some(&successor) = successors[node].get(*next) {
+                match visit[successor] {
+                    Visit::New => {
+                        *next += 1;
+                        visit[successor] = Visit::Active;
+                        stack.push((successor, 0));
+                    }
+                    Visit::Active => {
+                        successors[node].remove(*next);
+                    }
+                    Visit::Done => *next += 1,
+                }
+            } else {
+                visit[node] = Visit::Done;
+                order.push(node);
+                stack.pop();
+            }
+        }
+    }
+    order.reverse();
+
+    let mut rank = vec![0; items.len()];
+    let mut predecessors = vec![Vec::new(); items.len()];
+    for &node in &order {
+        for &successor in &successors[node] {
+            rank[successor] = rank[successor].max(rank[node] + 1);
+            predecessors[successor].push(node);
+        }
+    }
+
+    let rank_count = rank.iter().max().map_or(0, |max| max + 1);
+    let mut ranks = vec![Vec::new(); rank_count];
+    for (node, &rank) in rank.iter().enumerate() {
+        ranks[rank].push(node);
+    }
+
+    let mut position = vec![0.0; items.len()];
+    for rank in &mut ranks {
+        let barycenter: Vec<f32> = rank
+            .iter()
+            .enumerate()
+            .map(|(i, &node)| {
+                let preds = &predecessors[node];
+                if preds.is_empty() {
+                    i as f32
+                } else {
+                    preds.iter().map(|&pred| position[pred]).sum::<f32>() / preds.len() as f32
+                }
+            })
+            .collect();
+        let mut sorted: Vec<_> = rank.iter().copied().zip(barycenter).collect();
+        sorted.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        *rank = sorted.into_iter().map(|(node, _)| node).collect();
+        for (i, &node) in rank.iter().enumerate() {
+            position[node] = i as f32;
+        }
+    }
+
+    ranks
+        .into_iter()
+        .map(|rank| rank.into_iter().map(|node| items[node]).collect())
+        .collect()
+}
+
+#[test]
+fn layout_nested_clusters() {
+    let mut graph = LayoutGraph::default();
+    let outer = graph.add_cluster(None, 50.0, 20.0);
+    let inner = graph.add_cluster(Some(outer), 50.0, 20.0);
+    let a = graph.add_node(Some(inner), 40.0, 20.0);
+    let b = graph.add_node(Some(inner), 40.0, 20.0);
+    let c = graph.add_node(Some(outer), 40.0, 20.0);
+    let d = graph.add_node(None, 40.0, 20.0);
+    graph.add_edge(a, b);
+    graph.add_edge(inner, c);
+    graph.add_edge(c, d);
+    graph.add_edge(d, a); // cycle, must not hang
+
+    let rects = graph.layout(LayoutDirection::LeftRight, &LayoutSpacing::default());
+    let contains = |outer: &Rect, inner: &Rect| {
+        inner.x >= outer.x
+            && inner.y >= outer.y
+            && inner.x + inner.width <= outer.x + outer.width
+            && inner.y + inner.height <= outer.y + outer.height
+    };
+
+    assert!(contains(&rects[outer], &rects[inner]));
+    assert!(contains(&rects[inner], &rects[a]));
+    assert!(contains(&rects[inner], &rects[b]));
+    assert!(contains(&rects[outer], &rects[c]));
+    assert!(!contains(&rects[outer], &rects[d]));
+    assert!(rects[a].x + rects[a].width < rects[b].x);
+    assert!(rects[inner].x + rects[inner].width < rects[c].x);
+}
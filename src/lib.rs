@@ -1,15 +1,28 @@
 #![allow(clippy::needless_doctest_main)]
 #![allow(clippy::type_complexity)]
 
+use std::path::Path;
+
 use bevy_app::App;
-use bevy_ecs::schedule::{ScheduleLabel, Schedules};
+use bevy_ecs::{
+    schedule::{Schedule, ScheduleLabel, Schedules},
+    world::World,
+};
 
 #[cfg(feature = "cli")]
 mod cli;
 mod dot;
+mod json;
+#[cfg(feature = "svg")]
+mod layout;
 
 pub mod schedule_graph;
 
+use schedule_graph::{
+    ambiguity_baseline::{AmbiguityBaseline, AmbiguityCheck, CheckBaselineError},
+    path::{DependencyPaths, FindSystemError},
+};
+
 /// Formats the schedule into a dot graph.
 #[track_caller]
 pub fn schedule_graph_dot(
@@ -17,6 +30,325 @@ pub fn schedule_graph_dot(
     label: impl ScheduleLabel,
     settings: &schedule_graph::Settings,
 ) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_dot(schedule, world, settings)
+    })
+}
+
+/// Formats the components and resources the systems of the schedule write and read into a dot graph,
+/// to see which systems touch some data and in what order.
+/// See [`schedule_model_data_flow_dot`](schedule_graph::data_flow::schedule_model_data_flow_dot) for the edges.
+#[track_caller]
+pub fn schedule_data_flow_graph_dot(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_data_flow_dot(schedule, world, settings)
+    })
+}
+
+/// Formats the schedule as JSON, see [`schedule_graph::json::schedule_model_json`] for the schema.
+#[track_caller]
+pub fn schedule_graph_json(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_json(schedule, world, settings)
+    })
+}
+
+/// Formats the schedule as GraphML for graph analysis tools like yEd,
+/// see [`schedule_graph::graph_exchange::schedule_model_graphml`] for the attributes.
+#[track_caller]
+pub fn schedule_graph_graphml(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_graphml(schedule, world, settings)
+    })
+}
+
+/// Formats the schedule as GEXF for graph analysis tools like Gephi.
+#[track_caller]
+pub fn schedule_graph_gexf(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_gexf(schedule, world, settings)
+    })
+}
+
+/// Formats the schedule as a mermaid flowchart, which GitHub and GitLab render inline in markdown.
+#[track_caller]
+pub fn schedule_graph_mermaid(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_mermaid(schedule, world, settings)
+    })
+}
+
+/// Formats the schedule as an indented set hierarchy listing the dependencies of every system,
+/// for terminals and CI logs.
+#[track_caller]
+pub fn schedule_graph_text(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_text(schedule, world, settings)
+    })
+}
+
+/// Renders the schedule as a single offline HTML page with pan/zoom, collapsible sets, search
+/// and highlighting of a system's dependencies.
+#[cfg(feature = "html")]
+#[track_caller]
+pub fn schedule_graph_html(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_html(schedule, world, settings)
+    })
+}
+
+/// Lays out and renders the schedule as SVG in-process, without requiring the graphviz `dot` binary.
+#[cfg(feature = "svg")]
+#[track_caller]
+pub fn schedule_graph_svg(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_svg(schedule, world, settings)
+    })
+}
+
+/// Builds the format-independent [`ScheduleModel`](schedule_graph::ScheduleModel) of the schedule.
+#[track_caller]
+pub fn schedule_model(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> schedule_graph::ScheduleModel {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::ScheduleModel::new(schedule, world, settings)
+    })
+}
+
+/// Builds the model of the schedule with the `settings` applied, and an [unfiltered](schedule_graph::ScheduleModel::unfiltered)
+/// one to search orderings in.
+#[track_caller]
+pub(crate) fn schedule_models(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> (schedule_graph::ScheduleModel, schedule_graph::ScheduleModel) {
+    with_initialized_schedule(app, label, |schedule, world| {
+        (
+            schedule_graph::ScheduleModel::new(schedule, world, settings),
+            schedule_graph::ScheduleModel::unfiltered(schedule, world, settings),
+        )
+    })
+}
+
+/// Finds every ordering path between two systems of the schedule, given by name or full name,
+/// to explain why one runs after the other. See [`DependencyPaths::to_text`] for the explanation.
+///
+/// The filters of `settings` don't apply, so paths through systems and sets they would hide are found too.
+#[track_caller]
+pub fn schedule_dependency_paths(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    first: &str,
+    second: &str,
+    settings: &schedule_graph::Settings,
+) -> Result<DependencyPaths, FindSystemError> {
+    let model = with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::ScheduleModel::unfiltered(schedule, world, settings)
+    });
+    DependencyPaths::between(&model, first, second)
+}
+
+/// Formats the schedule into a dot graph with the ordering paths between two systems highlighted,
+/// see [`schedule_dependency_paths`].
+#[track_caller]
+pub fn schedule_graph_dependency_paths_dot(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    first: &str,
+    second: &str,
+    settings: &schedule_graph::Settings,
+) -> Result<String, FindSystemError> {
+    let (model, unfiltered) = schedule_models(app, label, settings);
+    let paths = DependencyPaths::between(&unfiltered, first, second)?;
+    Ok(schedule_graph::schedule_model_dot_highlighted(
+        &model,
+        settings,
+        &paths.highlight(settings),
+    ))
+}
+
+/// Analyses how parallel the schedule can run: its critical path, the width of each topological level and its exclusive systems.
+///
+/// The whole schedule is analysed, the filters of `settings` don't apply.
+///
+/// Use [`ScheduleAnalysis::with_weights`](schedule_graph::analysis::ScheduleAnalysis::with_weights) on an
+/// [unfiltered](schedule_graph::ScheduleModel::unfiltered) model for a critical path weighted by e.g. measured run times, and
+/// [`schedule_model_dot_highlighted`](schedule_graph::schedule_model_dot_highlighted) to draw it.
+#[track_caller]
+pub fn schedule_analysis(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> schedule_graph::analysis::ScheduleAnalysis {
+    let model = with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::ScheduleModel::unfiltered(schedule, world, settings)
+    });
+    schedule_graph::analysis::ScheduleAnalysis::new(&model)
+}
+
+/// Finds the systems writing and reading each message type of the schedule, and whether each reader sees the messages
+/// in the same frame or could read them a frame late.
+///
+/// See [`MessageFlow`](schedule_graph::message_flow::MessageFlow) for the text and dot reports.
+#[track_caller]
+pub fn schedule_message_flow(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> schedule_graph::message_flow::MessageFlow {
+    let (model, unfiltered) = schedule_models(app, label, settings);
+    schedule_graph::message_flow::MessageFlow::new(&model, &unfiltered)
+}
+
+/// Lists the schedules of the app and its sub-apps in the order they run, e.g. `Main` running `First`, `PreUpdate` and so on
+/// down to `Last`, followed by the extract and update schedules of the render app.
+///
+/// See [`AppOverview`](schedule_graph::overview::AppOverview) for the text and dot reports.
+pub fn app_overview(app: &App) -> schedule_graph::overview::AppOverview {
+    schedule_graph::overview::AppOverview::new(app)
+}
+
+/// Compares the schedule between two apps, e.g. before and after adding a plugin or updating bevy.
+///
+/// See [`ScheduleDiff`](schedule_graph::diff::ScheduleDiff) for the text, JSON and dot reports.
+#[track_caller]
+pub fn schedule_diff(
+    old: &mut App,
+    new: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> schedule_graph::diff::ScheduleDiff {
+    let label = label.intern();
+    let old = schedule_model(old, label, settings);
+    let new = schedule_model(new, label, settings);
+    schedule_graph::diff::ScheduleDiff::new(&old, &new)
+}
+
+/// Lists every ambiguity of the schedule, grouped by the conflicting component and by system and ranked by count.
+/// `settings.ambiguity_enable_on_world` includes conflicts on the `World`.
+#[track_caller]
+pub fn schedule_ambiguity_report(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> String {
+    with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::schedule_graph_ambiguity_report(schedule, world, settings)
+    })
+}
+
+/// Collects the ambiguities of the schedule, whether or not `settings.ambiguity_enable` is set.
+#[track_caller]
+pub fn ambiguity_baseline(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    settings: &schedule_graph::Settings,
+) -> AmbiguityBaseline {
+    let model = with_initialized_schedule(app, label, |schedule, world| {
+        schedule_graph::ScheduleModel::with_ambiguities(schedule, world, settings)
+    });
+    AmbiguityBaseline::new(&model)
+}
+
+/// Writes the current ambiguities of the schedule to a baseline file, to be checked with [`check_ambiguity_baseline`].
+#[track_caller]
+pub fn write_ambiguity_baseline(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    path: impl AsRef<Path>,
+    settings: &schedule_graph::Settings,
+) -> std::io::Result<()> {
+    let label_name = format!("{label:?}");
+    let baseline = ambiguity_baseline(app, label, settings);
+    std::fs::write(path, baseline.to_text(&label_name))
+}
+
+/// Fails if the schedule has ambiguities that are not listed in the baseline file,
+/// or if a listed pair conflicts on components that aren't listed for it.
+///
+/// Call this from a `#[test]` to catch new ambiguities like a lint:
+///
+/// ```rust,no_run
+/// # use bevy::prelude::*;
+/// # use bevy_mod_debugdump::schedule_graph::Settings;
+/// fn no_new_ambiguities() {
+///     let mut app = App::new();
+///     app.add_plugins(DefaultPlugins);
+///     let settings = Settings::default();
+///     bevy_mod_debugdump::check_ambiguity_baseline(&mut app, Update, "ambiguities.txt", &settings)
+///         .unwrap();
+/// }
+/// ```
+///
+/// Resolved ambiguities don't fail the check, but are returned to report them.
+#[track_caller]
+pub fn check_ambiguity_baseline(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    path: impl AsRef<Path>,
+    settings: &schedule_graph::Settings,
+) -> Result<AmbiguityCheck, CheckBaselineError> {
+    let text = std::fs::read_to_string(path).map_err(CheckBaselineError::Io)?;
+    let baseline = AmbiguityBaseline::parse(&text).map_err(CheckBaselineError::Parse)?;
+    let current = ambiguity_baseline(app, label, settings);
+
+    let check = baseline.check(&current);
+    if check.passed() {
+        Ok(check)
+    } else {
+        Err(CheckBaselineError::NewAmbiguities(check))
+    }
+}
+
+/// Prints the schedule with default settings.
+pub fn print_schedule_graph(app: &mut App, schedule_label: impl ScheduleLabel) {
+    let dot = schedule_graph_dot(app, schedule_label, &schedule_graph::Settings::default());
+    println!("{dot}");
+}
+
+/// Initializes the schedule for access info and conflicting systems, and passes it to `f`.
+#[track_caller]
+fn with_initialized_schedule<T>(
+    app: &mut App,
+    label: impl ScheduleLabel,
+    f: impl FnOnce(&Schedule, &World) -> T,
+) -> T {
     app.world_mut()
         .resource_scope::<Schedules, _>(|world, mut schedules| {
             let ignored_ambiguities = schedules.ignored_scheduling_ambiguities.clone();
@@ -31,15 +363,9 @@ pub fn schedule_graph_dot(
                 .graph_mut()
                 .build_schedule(world, &ignored_ambiguities);
 
-            schedule_graph::schedule_graph_dot(schedule, world, settings)
+            f(schedule, world)
         })
 }
 
-/// Prints the schedule with default settings.
-pub fn print_schedule_graph(app: &mut App, schedule_label: impl ScheduleLabel) {
-    let dot = schedule_graph_dot(app, schedule_label, &schedule_graph::Settings::default());
-    println!("{dot}");
-}
-
 #[cfg(feature = "cli")]
 pub use cli::CommandLineArgs;
@@ -0,0 +1,337 @@
+//! Checked-in list of known ambiguities, to fail CI when new ones are introduced.
+//!
+//! The baseline is a plain text file with one system pair per line, followed by the conflicting
+//! components and resources indented below it:
+//!
+//! ```text
+//! # ambiguities in Update
+//! my_game::apply_velocity <-> my_game::collide
+//!     my_game::Velocity
+//! my_game::save_game <-> my_game::tick
+//!     World
+//! ```
+//!
+//! Systems are identified by [`Settings::full_system_name`](super::Settings::full_system_name),
+//! so keep the settings the same when writing and checking the baseline.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::model::ScheduleModel;
+
+/// Marker for ambiguities on the whole `World` instead of specific components
+const WORLD: &str = "World";
+
+/// What a pair of ambiguous systems conflicts on
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Conflict {
+    /// The whole `World`, e.g. when one of the systems takes `&mut World`
+    World,
+    /// A component or resource, by its full type name
+    Component(String),
+}
+
+impl Conflict {
+    fn parse(name: &str) -> Conflict {
+        match name {
+            WORLD => Conflict::World,
+            _ => Conflict::Component(name.to_owned()),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            Conflict::World => WORLD,
+            Conflict::Component(name) => name,
+        }
+    }
+}
+
+/// A set of ambiguous system pairs and what they conflict on
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct AmbiguityBaseline {
+    /// Maps `(system_a, system_b)`, ordered by name, to the sorted conflicts.
+    pub ambiguities: BTreeMap<(String, String), Vec<Conflict>>,
+}
+
+impl AmbiguityBaseline {
+    /// Collects the ambiguities of the model, which must be built with [`ScheduleModel::with_ambiguities`]
+    /// or [`Settings::ambiguity_enable`](super::Settings::ambiguity_enable).
+    pub fn new(model: &ScheduleModel) -> AmbiguityBaseline {
+        let mut ambiguities = BTreeMap::new();
+        for ambiguity in &model.ambiguities {
+            let a = model.system(ambiguity.system_a).unwrap().full_name.clone();
+            let b = model.system(ambiguity.system_b).unwrap().full_name.clone();
+            let pair = if a <= b { (a, b) } else { (b, a) };
+
+            let conflicts: &mut Vec<Conflict> = ambiguities.entry(pair).or_default();
+            if ambiguity.conflicts.is_empty() {
+                conflicts.push(Conflict::World);
+            }
+            conflicts.extend(ambiguity.conflicts.iter().cloned().map(Conflict::Component));
+            conflicts.sort();
+            conflicts.dedup();
+        }
+        AmbiguityBaseline { ambiguities }
+    }
+
+    /// Parses a baseline in the format written by [`AmbiguityBaseline::to_text`]
+    pub fn parse(text: &str) -> Result<AmbiguityBaseline, ParseBaselineError> {
+        let mut ambiguities = BTreeMap::new();
+        let mut current: Option<&mut Vec<Conflict>> = None;
+
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with(char::is_whitespace) {
+                let Some(conflicts) = current.as_mut() else {
+                    return Err(ParseBaselineError {
+                        line: i + 1,
+                        message: "conflict listed before any system pair".into(),
+                    });
+                };
+                conflicts.push(Conflict::parse(trimmed));
+                conflicts.sort();
+                conflicts.dedup();
+            } else {
+                let Some((a, b)) = trimmed.split_once(" <-> ") else {
+                    return Err(ParseBaselineError {
+                        line: i + 1,
+                        message: "expected `system_a <-> system_b`".into(),
+                    });
+                };
+                let (a, b) = (a.trim().to_owned(), b.trim().to_owned());
+                let pair = if a <= b { (a, b) } else { (b, a) };
+                current = Some(ambiguities.entry(pair).or_default());
+            }
+        }
+
+        Ok(AmbiguityBaseline { ambiguities })
+    }
+
+    /// Formats the baseline to be checked in
+    pub fn to_text(&self, label: &str) -> String {
+        let mut out = format!("# ambiguities in {label}\n");
+        for ((a, b), conflicts) in &self.ambiguities {
+            let _ = writeln!(out, "{a} <-> {b}");
+            for conflict in conflicts {
+                let _ = writeln!(out, "    {}", conflict.name());
+            }
+        }
+        out
+    }
+
+    /// Compares the `current` ambiguities against this baseline.
+    ///
+    /// Pairs that are in the baseline but now conflict on additional components are reported as new, with only the added conflicts.
+    pub fn check(&self, current: &AmbiguityBaseline) -> AmbiguityCheck {
+        let mut check = AmbiguityCheck::default();
+        for (pair, conflicts) in &current.ambiguities {
+            let Some(known) = self.ambiguities.get(pair) else {
+                check.new.insert(pair.clone(), conflicts.clone());
+                continue;
+            };
+            // a known conflict on the whole `World` covers every component
+            let world_known = known.contains(&Conflict::World);
+            let added: Vec<Conflict> = conflicts
+                .iter()
+                .filter(|conflict| !known.contains(conflict) && !world_known)
+                .cloned()
+                .collect();
+            if !added.is_empty() {
+                check.new.insert(pair.clone(), added);
+            }
+        }
+        for (pair, conflicts) in &self.ambiguities {
+            if !current.ambiguities.contains_key(pair) {
+                check.resolved.insert(pair.clone(), conflicts.clone());
+            }
+        }
+        check
+    }
+}
+
+/// Result of [`AmbiguityBaseline::check`]
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct AmbiguityCheck {
+    /// Ambiguities that are not in the baseline, or the conflicts a known pair gained since
+    pub new: BTreeMap<(String, String), Vec<Conflict>>,
+    /// Ambiguities in the baseline that don't exist anymore.
+    /// These don't fail the check, but the baseline should be updated to keep them from coming back.
+    pub resolved: BTreeMap<(String, String), Vec<Conflict>>,
+}
+
+impl AmbiguityCheck {
+    /// Whether no new ambiguities were introduced
+    pub fn passed(&self) -> bool {
+        self.new.is_empty()
+    }
+}
+
+impl std::fmt::Display for AmbiguityCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sections = [
+            ("new ambiguities, order these systems", &self.new),
+            ("resolved ambiguities, update the baseline", &self.resolved),
+        ];
+        for (title, ambiguities) in sections {
+            if ambiguities.is_empty() {
+                continue;
+            }
+            writeln!(f, "{} {title}:", ambiguities.len())?;
+            for ((a, b), conflicts) in ambiguities {
+                let conflicts = conflicts
+                    .iter()
+                    .map(|conflict| disqualified::ShortName(conflict.name()).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                writeln!(f, "  {a} <-> {b}: {conflicts}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseBaselineError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseBaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "invalid ambiguity baseline at line {}: {}",
+            self.line, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseBaselineError {}
+
+/// Error of [`check_ambiguity_baseline`](crate::check_ambiguity_baseline)
+pub enum CheckBaselineError {
+    Io(std::io::Error),
+    Parse(ParseBaselineError),
+    /// The schedule has ambiguities that are not in the baseline
+    NewAmbiguities(AmbiguityCheck),
+}
+
+impl std::fmt::Debug for CheckBaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckBaselineError::Io(error) => {
+                write!(f, "failed to read ambiguity baseline: {error}")
+            }
+            CheckBaselineError::Parse(error) => write!(f, "{error}"),
+            CheckBaselineError::NewAmbiguities(check) => write!(f, "{check}"),
+        }
+    }
+}
+
+impl std::fmt::Display for CheckBaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as std::fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl std::error::Error for CheckBaselineError {}
+
+#[test]
+fn baseline_roundtrip() {
+    let text = "# ambiguities in Update\n\
+        a::x <-> a::y\n    a::Transform\n    a::Velocity\n\
+        b::save <-> a::x\n    World\n";
+    let baseline = AmbiguityBaseline::parse(text).unwrap();
+    assert_eq!(baseline.ambiguities.len(), 2);
+    assert_eq!(
+        baseline.ambiguities[&("a::x".to_owned(), "b::save".to_owned())],
+        [Conflict::World]
+    );
+    assert_eq!(
+        AmbiguityBaseline::parse(&baseline.to_text("Update")).unwrap(),
+        baseline
+    );
+
+    let mut current = baseline.clone();
+    current.ambiguities.insert(
+        ("a::x".to_owned(), "a::z".to_owned()),
+        vec![Conflict::World],
+    );
+    assert!(baseline.check(&baseline).passed());
+    assert!(!baseline.check(&current).passed());
+    assert!(current.check(&baseline).passed());
+
+    // conflicting on an additional component is reported, but only with the new component
+    let mut current = baseline.clone();
+    let pair = ("a::x".to_owned(), "a::y".to_owned());
+    current.ambiguities.insert(
+        pair.clone(),
+        vec![
+            Conflict::Component("a::Mass".to_owned()),
+            Conflict::Component("a::Transform".to_owned()),
+        ],
+    );
+    let check = baseline.check(&current);
+    assert_eq!(
+        check.new[&pair],
+        [Conflict::Component("a::Mass".to_owned())]
+    );
+    assert!(check.resolved.is_empty());
+
+    // a pair conflicting on components and on the `World` keeps both
+    let mut current = baseline.clone();
+    current
+        .ambiguities
+        .get_mut(&pair)
+        .unwrap()
+        .insert(0, Conflict::World);
+    let check = baseline.check(&current);
+    assert_eq!(check.new[&pair], [Conflict::World]);
+    assert_eq!(
+        AmbiguityBaseline::parse(&current.to_text("Update")).unwrap(),
+        current
+    );
+    assert!(current.check(&current).passed());
+}
+
+#[test]
+fn baseline_merges_world_conflicts() {
+    use bevy_ecs::prelude::*;
+
+    use super::{model::Ambiguity, Settings};
+
+    #[derive(Resource)]
+    struct Gravity;
+
+    fn apply_gravity(_: ResMut<Gravity>) {}
+    fn tweak_gravity(_: ResMut<Gravity>) {}
+
+    let (schedule, world) = super::model::test_schedule((apply_gravity, tweak_gravity));
+
+    let settings = Settings {
+        ambiguity_enable: true,
+        ..Settings::default()
+    };
+    let mut model = ScheduleModel::new(&schedule, &world, &settings);
+    let known = AmbiguityBaseline::new(&model);
+
+    // the same pair also conflicting on the `World`, e.g. for two systems of the same name
+    let ambiguity = &model.ambiguities[0];
+    let (system_a, system_b) = (ambiguity.system_a, ambiguity.system_b);
+    model.ambiguities.push(Ambiguity {
+        system_a,
+        system_b,
+        conflicts: Vec::new(),
+    });
+    let current = AmbiguityBaseline::new(&model);
+
+    let conflicts = current.ambiguities.values().next().unwrap();
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0], Conflict::World);
+    let check = known.check(&current);
+    assert_eq!(check.new.values().next().unwrap(), &[Conflict::World]);
+}
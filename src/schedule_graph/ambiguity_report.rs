@@ -0,0 +1,155 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::model::ScheduleModel;
+
+/// Name of the group of ambiguities on the whole `World`
+const WORLD: &str = "World";
+
+/// Every ambiguity of a schedule, grouped by what the systems conflict on and by system.
+///
+/// Groups are ranked by the number of ambiguities in them, so the most contended components
+/// and most problematic systems come first.
+pub struct AmbiguityReport {
+    pub label: String,
+    /// Total number of ambiguous system pairs
+    pub count: usize,
+    /// Conflicting component or resource, or `World`, with the system pairs conflicting on it
+    pub by_component: Vec<(String, Vec<(String, String)>)>,
+    /// System with the systems it is ambiguous with, and what they conflict on
+    pub by_system: Vec<(String, Vec<(String, Vec<String>)>)>,
+}
+
+impl AmbiguityReport {
+    /// Builds the report from the ambiguities of the model,
+    /// which must be built with [`ScheduleModel::with_ambiguities`] or [`Settings::ambiguity_enable`](super::Settings::ambiguity_enable).
+    /// Conflicts on the `World` are only included with [`Settings::ambiguity_enable_on_world`](super::Settings::ambiguity_enable_on_world).
+    pub fn new(model: &ScheduleModel) -> AmbiguityReport {
+        let mut by_component = BTreeMap::<String, Vec<(String, String)>>::new();
+        let mut by_system = BTreeMap::<String, Vec<(String, Vec<String>)>>::new();
+
+        for ambiguity in &model.ambiguities {
+            let a = model.node_name(ambiguity.system_a).to_owned();
+            let b = model.node_name(ambiguity.system_b).to_owned();
+            let conflicts: Vec<String> = if ambiguity.conflicts.is_empty() {
+                vec![WORLD.to_owned()]
+            } else {
+                ambiguity
+                    .conflicts
+                    .iter()
+                    .map(|name| disqualified::ShortName(name).to_string())
+                    .collect()
+            };
+
+            for conflict in &conflicts {
+                by_component
+                    .entry(conflict.clone())
+                    .or_default()
+                    .push((a.clone(), b.clone()));
+            }
+            by_system
+                .entry(a.clone())
+                .or_default()
+                .push((b.clone(), conflicts.clone()));
+            by_system.entry(b).or_default().push((a, conflicts));
+        }
+
+        AmbiguityReport {
+            label: model.label.clone(),
+            count: model.ambiguities.len(),
+            by_component: ranked(by_component),
+            by_system: ranked(by_system),
+        }
+    }
+
+    /// Formats the report as indented text
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "{} ambiguities in {}", self.count, self.label);
+        if self.count == 0 {
+            return out;
+        }
+
+        let _ = writeln!(out, "\nBy component");
+        for (component, pairs) in &self.by_component {
+            let _ = writeln!(out, "  {component} ({})", pairs.len());
+            for (a, b) in pairs {
+                let _ = writeln!(out, "    {a} <-> {b}");
+            }
+        }
+
+        let _ = writeln!(out, "\nBy system");
+        for (system, others) in &self.by_system {
+            let _ = writeln!(out, "  {system} ({})", others.len());
+            for (other, conflicts) in others {
+                let _ = writeln!(out, "    <-> {other}: {}", conflicts.join(", "));
+            }
+        }
+
+        out
+    }
+}
+
+/// Sorts groups by descending size, then by name
+fn ranked<T: Ord>(groups: BTreeMap<String, Vec<T>>) -> Vec<(String, Vec<T>)> {
+    let mut groups: Vec<_> = groups
+        .into_iter()
+        .map(|(name, mut items)| {
+            items.sort();
+            (name, items)
+        })
+        .collect();
+    groups
+        .sort_by(|(a_name, a), (b_name, b)| b.len().cmp(&a.len()).then_with(|| a_name.cmp(b_name)));
+    groups
+}
+
+#[test]
+fn report_grouping() {
+    use bevy_ecs::prelude::*;
+
+    use super::Settings;
+
+    #[derive(Resource)]
+    struct Gravity;
+    #[derive(Resource)]
+    struct Score;
+
+    fn apply_gravity(_: ResMut<Gravity>) {}
+    fn tweak_gravity(_: ResMut<Gravity>) {}
+    fn update_all(_: ResMut<Gravity>, _: ResMut<Score>) {}
+    fn count_score(_: ResMut<Score>) {}
+
+    let (schedule, world) =
+        super::model::test_schedule((apply_gravity, tweak_gravity, update_all, count_score));
+
+    let settings = Settings {
+        ambiguity_enable: true,
+        ..Settings::default()
+    };
+    let model = ScheduleModel::new(&schedule, &world, &settings);
+    let report = AmbiguityReport::new(&model);
+
+    assert_eq!(report.count, 4);
+    assert_eq!(
+        report
+            .by_component
+            .iter()
+            .map(|(name, pairs)| (name.as_str(), pairs.len()))
+            .collect::<Vec<_>>(),
+        [("Gravity", 3), ("Score", 1)]
+    );
+    // ties are sorted by name
+    assert_eq!(
+        report
+            .by_system
+            .iter()
+            .map(|(name, others)| (name.as_str(), others.len()))
+            .collect::<Vec<_>>(),
+        [
+            ("update_all", 3),
+            ("apply_gravity", 2),
+            ("tweak_gravity", 2),
+            ("count_score", 1),
+        ]
+    );
+}
@@ -0,0 +1,334 @@
+//! Critical path and parallelism of a schedule, to see how parallel it could run before profiling it.
+
+use std::fmt::Write;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::{hash_map::HashMap, hash_set::HashSet};
+
+use super::{
+    model::{ScheduleModel, SystemNode},
+    path::neighbors,
+    Highlight, Settings,
+};
+
+/// Numbers on how parallel a schedule can run, ignoring conflicting data access.
+///
+/// Orderings of sets apply to all systems in them, so only the order between systems is analysed.
+/// Every system of the model is analysed, and orderings through systems and sets missing from it are lost,
+/// so build it with [`ScheduleModel::unfiltered`] and only draw the [highlight](ScheduleAnalysis::highlight) on a filtered model.
+pub struct ScheduleAnalysis {
+    pub label: String,
+    /// Number of included systems
+    pub system_count: usize,
+    /// Longest chain of systems that have to run one after another
+    pub critical_path: Vec<NodeId>,
+    /// Heaviest chain of systems by the weights passed to [`ScheduleAnalysis::with_weights`], and its total weight
+    pub weighted_critical_path: Option<(Vec<NodeId>, f64)>,
+    /// Number of systems in each topological level. The level of a system is the length of the longest chain before it,
+    /// so systems of the same level don't depend on each other and could run in parallel.
+    pub level_widths: Vec<usize>,
+    /// Systems that need exclusive `World` access, so nothing runs in parallel with them
+    pub exclusive_systems: Vec<NodeId>,
+
+    /// Dependency edges between the systems of the highlighted path
+    path_dependencies: HashSet<(NodeId, NodeId)>,
+    names: HashMap<NodeId, String>,
+}
+
+impl ScheduleAnalysis {
+    pub fn new(model: &ScheduleModel) -> ScheduleAnalysis {
+        Self::analyse(model, None::<fn(&SystemNode) -> f64>)
+    }
+
+    /// Also computes the heaviest chain of systems, e.g. with the measured run time of each system as `weight`
+    pub fn with_weights(
+        model: &ScheduleModel,
+        weight: impl Fn(&SystemNode) -> f64,
+    ) -> ScheduleAnalysis {
+        Self::analyse(model, Some(weight))
+    }
+
+    fn analyse(model: &ScheduleModel, weight: Option<impl Fn(&SystemNode) -> f64>) -> Self {
+        let graph = SystemOrder::new(model);
+
+        let (critical_path, levels) = graph.longest_path(|_| 1.0);
+        let mut level_widths = Vec::new();
+        for level in levels.into_values() {
+            let level = level as usize - 1;
+            if level_widths.len() <= level {
+                level_widths.resize(level + 1, 0);
+            }
+            level_widths[level] += 1;
+        }
+
+        let weighted_critical_path = weight.map(|weight| {
+            let (path, weights) = graph.longest_path(|id| weight(model.system(id).unwrap()));
+            let total = path.last().map_or(0.0, |last| weights[last]);
+            (path, total)
+        });
+
+        let highlighted = weighted_critical_path
+            .as_ref()
+            .map_or(&critical_path, |(path, _)| path);
+        let path_dependencies = highlighted
+            .windows(2)
+            .filter_map(|pair| graph.cause.get(&(pair[0], pair[1])).copied())
+            .collect();
+
+        ScheduleAnalysis {
+            label: model.label.clone(),
+            system_count: model.systems.len(),
+            exclusive_systems: model
+                .systems
+                .iter()
+                .filter(|system| system.exclusive)
+                .map(|system| system.id)
+                .collect(),
+            names: model
+                .systems
+                .iter()
+                .map(|system| (system.id, system.name.clone()))
+                .collect(),
+            critical_path,
+            weighted_critical_path,
+            level_widths,
+            path_dependencies,
+        }
+    }
+
+    /// The critical path, or the weighted one if there are weights,
+    /// to render with [`schedule_model_dot_highlighted`](super::schedule_model_dot_highlighted)
+    pub fn highlight(&self, settings: &Settings) -> Highlight {
+        let path = self
+            .weighted_critical_path
+            .as_ref()
+            .map_or(&self.critical_path, |(path, _)| path);
+        Highlight {
+            nodes: path.iter().copied().collect(),
+            dependencies: self.path_dependencies.clone(),
+            color: settings.style.color_highlight.clone(),
+        }
+    }
+
+    /// Formats the report as indented text
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}: {} systems, {} exclusive",
+            self.label,
+            self.system_count,
+            self.exclusive_systems.len()
+        );
+
+        let _ = writeln!(out, "\nCritical path: {} systems", self.critical_path.len());
+        for system in &self.critical_path {
+            let _ = writeln!(out, "  {}", self.names[system]);
+        }
+
+        if let Some((path, total)) = &self.weighted_critical_path {
+            let _ = writeln!(out, "\nWeighted critical path: {total}");
+            for system in path {
+                let _ = writeln!(out, "  {}", self.names[system]);
+            }
+        }
+
+        let max_width = self.level_widths.iter().copied().max().unwrap_or(0);
+        let average = if self.level_widths.is_empty() {
+            0.0
+        } else {
+            self.system_count as f64 / self.level_widths.len() as f64
+        };
+        let _ = writeln!(
+            out,
+            "\n{} levels, at most {max_width} and on average {average:.2} systems per level",
+            self.level_widths.len()
+        );
+        for (level, width) in self.level_widths.iter().enumerate() {
+            let _ = writeln!(out, "  {level}: {width}");
+        }
+
+        if !self.exclusive_systems.is_empty() {
+            let _ = writeln!(out, "\nExclusive systems");
+            for system in &self.exclusive_systems {
+                let _ = writeln!(out, "  {}", self.names[system]);
+            }
+        }
+
+        out
+    }
+}
+
+/// Orderings between the systems of a model, with the orderings of sets applied to the systems in them.
+///
+/// Built from an [unfiltered](ScheduleModel::unfiltered) model, so that systems and sets hidden by filters still order the others.
+pub(super) struct SystemOrder {
+    /// Systems in topological order
+    order: Vec<NodeId>,
+    successors: HashMap<NodeId, Vec<NodeId>>,
+    /// The dependency edge each ordering comes from
+    cause: HashMap<(NodeId, NodeId), (NodeId, NodeId)>,
+}
+
+impl SystemOrder {
+    pub(super) fn new(model: &ScheduleModel) -> Self {
+        let (parents, children) = model.parents_and_children();
+        let mut dependencies = HashMap::<NodeId, Vec<NodeId>>::default();
+        for edge in &model.dependencies {
+            dependencies.entry(edge.from).or_default().push(edge.to);
+        }
+
+        let mut successors = HashMap::<NodeId, Vec<NodeId>>::default();
+        let mut cause = HashMap::default();
+        for system in &model.systems {
+            for ancestor in reachable(system.id, &parents) {
+                for &next in neighbors(&dependencies, ancestor) {
+                    for next_system in reachable(next, &children) {
+                        let pair = (system.id, next_system);
+                        if next_system.is_system()
+                            && next_system != system.id
+                            && !cause.contains_key(&pair)
+                        {
+                            cause.insert(pair, (ancestor, next));
+                            successors.entry(system.id).or_default().push(next_system);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Kahn's algorithm, in the order of the model for stable results
+        let mut in_degree = HashMap::<NodeId, usize>::default();
+        for &next in successors.values().flatten() {
+            *in_degree.entry(next).or_default() += 1;
+        }
+        let mut ready: Vec<NodeId> = model
+            .systems
+            .iter()
+            .map(|system| system.id)
+            .filter(|id| !in_degree.contains_key(id))
+            .rev()
+            .collect();
+        let mut order = Vec::with_capacity(model.systems.len());
+        while let Some(system) = ready.pop() {
+            order.push(system);
+            for &next in neighbors(&successors, system) {
+                let degree = in_degree.get_mut(&next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+
+        SystemOrder {
+            order,
+            successors,
+            cause,
+        }
+    }
+
+    /// The system and all systems that have to run after it
+    pub(super) fn runs_before(&self, system: NodeId) -> HashSet<NodeId> {
+        reachable(system, &self.successors).into_iter().collect()
+    }
+
+    /// The heaviest chain of systems, and the weight of the heaviest chain ending at each system
+    fn longest_path(&self, weight: impl Fn(NodeId) -> f64) -> (Vec<NodeId>, HashMap<NodeId, f64>) {
+        let mut distance: HashMap<NodeId, f64> =
+            self.order.iter().map(|&id| (id, weight(id))).collect();
+        let mut previous = HashMap::<NodeId, NodeId>::default();
+        for &system in &self.order {
+            let reached = distance[&system];
+            for &next in neighbors(&self.successors, system) {
+                let through = reached + weight(next);
+                if through > distance[&next] {
+                    distance.insert(next, through);
+                    previous.insert(next, system);
+                }
+            }
+        }
+
+        let mut last = None;
+        for &system in &self.order {
+            if last.is_none_or(|last| distance[&system] > distance[&last]) {
+                last = Some(system);
+            }
+        }
+        let mut path: Vec<_> =
+            std::iter::successors(last, |system| previous.get(system).copied()).collect();
+        path.reverse();
+        (path, distance)
+    }
+}
+
+/// The node and every node reachable from it in `edges`
+fn reachable(node: NodeId, edges: &HashMap<NodeId, Vec<NodeId>>) -> Vec<NodeId> {
+    let mut visited = HashSet::<NodeId>::default();
+    let mut stack = vec![node];
+    let mut reachable = Vec::new();
+    while let Some(node) = stack.pop() {
+        if visited.insert(node) {
+            reachable.push(node);
+            stack.extend(neighbors(edges, node));
+        }
+    }
+    reachable
+}
+
+#[test]
+fn system_order() {
+    use bevy_ecs::prelude::*;
+
+    use super::{filter::Filter, settings::FocusDirection};
+
+    #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+    struct Physics;
+
+    fn input() {}
+    fn gravity() {}
+    fn collide() {}
+    fn render() {}
+    fn audio() {}
+
+    let (schedule, world) = super::model::test_schedule((
+        input.before(Physics),
+        (gravity, collide).chain().in_set(Physics),
+        render.after(collide),
+        audio,
+    ));
+
+    // `gravity` and `collide` are hidden, but still order `input` before `render`
+    let settings = Settings::default().focus_on(
+        Filter::system("*::input").or(Filter::system("*::render")),
+        0,
+        FocusDirection::Both,
+    );
+    let filtered = ScheduleModel::new(&schedule, &world, &settings);
+    assert_eq!(filtered.systems.len(), 2);
+    let model = ScheduleModel::unfiltered(&schedule, &world, &settings);
+    let id = |name: &str| super::path::find_system(&model, name).unwrap();
+    let names = |ids: &[NodeId]| -> Vec<String> {
+        ids.iter()
+            .map(|&id| model.node_name(id).to_owned())
+            .collect()
+    };
+
+    let order = SystemOrder::new(&model);
+    let mut after_input = names(
+        &order
+            .runs_before(id("input"))
+            .into_iter()
+            .collect::<Vec<_>>(),
+    );
+    after_input.sort();
+    assert_eq!(after_input, ["collide", "gravity", "input", "render"]);
+    assert_eq!(order.runs_before(id("audio")).len(), 1);
+
+    let analysis = ScheduleAnalysis::new(&model);
+    assert_eq!(
+        names(&analysis.critical_path),
+        ["input", "gravity", "collide", "render"]
+    );
+    assert_eq!(analysis.level_widths, [2, 1, 1, 1]);
+}
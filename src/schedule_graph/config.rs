@@ -0,0 +1,315 @@
+//! [`Settings`] loaded from a TOML file, to tweak diagrams without recompiling.
+//!
+//! ```toml
+//! collapse_single_system_sets = true
+//! remove_transitive_edges = true
+//! show_access = true
+//! show_conditions = true
+//! ambiguity_enable = true
+//! ambiguity_enable_on_world = false
+//! # globs on the full system names, and on the names of the sets systems are in
+//! include = ["my_game::*"]
+//! exclude = ["my_game::debug::*"]
+//! include_sets = ["PhysicsSet*"]
+//! exclude_sets = []
+//!
+//! [style]
+//! preset = "light" # or dark_github, dark_discord
+//! rankdir = "TD"
+//! edge_style = "ortho"
+//! fontname = "Fira Sans"
+//! color_background = "#ffffff"
+//! color_set = "#00000008"
+//! color_set_label = "#000000"
+//! color_set_border = "#00000040"
+//! color_edge = ["#eede00", "#881877", "#00b0cc"]
+//! multiple_set_edge_color = "blue"
+//! ambiguity_color = "#c93526"
+//! ambiguity_bgcolor = "#d3d3d3"
+//! color_highlight = "#e36209"
+//! color_condition = "#6f42c1"
+//! penwidth_edge = 1.5
+//!
+//! # only show systems within `depth` dependency hops of the matching systems
+//! [focus]
+//! systems = ["*apply_velocity"]
+//! sets = []
+//! depth = 2
+//! direction = "upstream" # or downstream, both
+//!
+//! # background color of the systems of a crate
+//! [crate_colors]
+//! my_game = "#ffcc00"
+//! ```
+//!
+//! Every key is optional, and unknown keys are an error.
+//!
+//! Requires the `config` feature, which is enabled by the `cli` feature.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use bevy_color::{Color, Srgba};
+use serde::{de::Error as _, Deserialize, Deserializer};
+
+use super::{
+    filter::Filter,
+    graph_exchange::crate_name,
+    settings::{EdgeStyle, FocusDirection, RankDir, Settings, Style},
+    system_style::system_to_style,
+};
+
+/// Settings that can be read from a config file, see the [module docs](self) for the format.
+///
+/// Unset keys keep the value of the [`Settings`] the config is applied to.
+#[derive(Default, Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SettingsConfig {
+    pub collapse_single_system_sets: Option<bool>,
+    pub remove_transitive_edges: Option<bool>,
+    pub show_access: Option<bool>,
+    pub show_conditions: Option<bool>,
+    pub ambiguity_enable: Option<bool>,
+    pub ambiguity_enable_on_world: Option<bool>,
+    /// Only include systems whose full name matches one of these globs, or that are in one of `include_sets`
+    pub include: Vec<String>,
+    /// Exclude systems whose full name matches one of these globs
+    pub exclude: Vec<String>,
+    /// Only include systems in a set whose name matches one of these globs, or that match `include`
+    pub include_sets: Vec<String>,
+    /// Exclude systems in a set whose name matches one of these globs
+    pub exclude_sets: Vec<String>,
+
+    /// The `[style]` table
+    pub style: StyleConfig,
+    /// The `[focus]` table
+    pub focus: FocusConfig,
+    /// Crate name and the background color of its systems
+    #[serde(deserialize_with = "crate_colors")]
+    pub crate_colors: Vec<(String, Color)>,
+}
+
+/// The `[style]` table of the config, see [`Style`]
+#[derive(Default, Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    /// Name of the [`Style`] preset the other keys are applied to
+    #[serde(deserialize_with = "parsed")]
+    pub preset: Option<Style>,
+    #[serde(deserialize_with = "parsed")]
+    pub rankdir: Option<RankDir>,
+    #[serde(deserialize_with = "parsed")]
+    pub edge_style: Option<EdgeStyle>,
+    pub fontname: Option<String>,
+    pub color_background: Option<String>,
+    pub color_set: Option<String>,
+    pub color_set_label: Option<String>,
+    pub color_set_border: Option<String>,
+    pub color_edge: Option<Vec<String>>,
+    pub multiple_set_edge_color: Option<String>,
+    pub ambiguity_color: Option<String>,
+    pub ambiguity_bgcolor: Option<String>,
+    pub color_highlight: Option<String>,
+    pub color_condition: Option<String>,
+    pub penwidth_edge: Option<f32>,
+}
+
+/// The `[focus]` table of the config, see [`Focus`](super::settings::Focus)
+#[derive(Default, Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FocusConfig {
+    /// Globs for the systems to focus on
+    pub systems: Vec<String>,
+    /// Globs for the sets whose systems to focus on
+    pub sets: Vec<String>,
+    pub depth: Option<usize>,
+    #[serde(deserialize_with = "parsed")]
+    pub direction: Option<FocusDirection>,
+}
+
+impl SettingsConfig {
+    /// Parses a config in the format described in the [module docs](self)
+    pub fn parse(text: &str) -> Result<SettingsConfig, ParseConfigError> {
+        toml::from_str(text).map_err(|error| ParseConfigError {
+            line: error
+                .span()
+                .map(|span| text[..span.start].matches('\n').count() + 1),
+            message: error.message().to_owned(),
+        })
+    }
+
+    /// Overrides the values of `settings` that are set in this config
+    pub fn apply(&self, mut settings: Settings) -> Settings {
+        let config = &self.style;
+        if let Some(preset) = &config.preset {
+            settings.style.clone_from(preset);
+        }
+        let style = &mut settings.style;
+        if let Some(rankdir) = config.rankdir {
+            style.schedule_rankdir = rankdir;
+        }
+        if let Some(edge_style) = config.edge_style {
+            style.edge_style = edge_style;
+        }
+        let strings = [
+            (&config.fontname, &mut style.fontname),
+            (&config.color_background, &mut style.color_background),
+            (&config.color_set, &mut style.color_set),
+            (&config.color_set_label, &mut style.color_set_label),
+            (&config.color_set_border, &mut style.color_set_border),
+            (
+                &config.multiple_set_edge_color,
+                &mut style.multiple_set_edge_color,
+            ),
+            (&config.ambiguity_color, &mut style.ambiguity_color),
+            (&config.ambiguity_bgcolor, &mut style.ambiguity_bgcolor),
+            (&config.color_highlight, &mut style.color_highlight),
+            (&config.color_condition, &mut style.color_condition),
+        ];
+        for (value, field) in strings {
+            if let Some(value) = value {
+                field.clone_from(value);
+            }
+        }
+        if let Some(color_edge) = &config.color_edge {
+            style.color_edge.clone_from(color_edge);
+        }
+        if let Some(penwidth_edge) = config.penwidth_edge {
+            style.penwidth_edge = penwidth_edge;
+        }
+
+        let flags = [
+            (
+                self.collapse_single_system_sets,
+                &mut settings.collapse_single_system_sets,
+            ),
+            (
+                self.remove_transitive_edges,
+                &mut settings.remove_transitive_edges,
+            ),
+            (self.show_access, &mut settings.show_access),
+            (self.show_conditions, &mut settings.show_conditions),
+            (self.ambiguity_enable, &mut settings.ambiguity_enable),
+            (
+                self.ambiguity_enable_on_world,
+                &mut settings.ambiguity_enable_on_world,
+            ),
+        ];
+        for (value, field) in flags {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+
+        if let Some(include) = Filter::any_of(&self.include, &self.include_sets) {
+            settings = settings.include(include);
+        }
+        if let Some(exclude) = Filter::any_of(&self.exclude, &self.exclude_sets) {
+            settings = settings.exclude(exclude);
+        }
+
+        let focus = &self.focus;
+        if let Some(filter) = Filter::any_of(&focus.systems, &focus.sets) {
+            settings = settings.focus_on(
+                filter,
+                focus.depth.unwrap_or(1),
+                focus.direction.unwrap_or_default(),
+            );
+        }
+
+        if !self.crate_colors.is_empty() {
+            let crate_colors = self.crate_colors.clone();
+            settings.system_style = Box::new(move |system| {
+                let mut style = system_to_style(system);
+                let name = system.name().to_string();
+                let crate_name = crate_name(&name);
+                if let Some((_, color)) = crate_colors.iter().find(|(c, _)| c == crate_name) {
+                    style.bg_color = *color;
+                }
+                style
+            });
+        }
+
+        settings
+    }
+
+    /// Builds [`Settings`] from the defaults and this config
+    pub fn settings(&self) -> Settings {
+        self.apply(Settings::default())
+    }
+}
+
+/// Deserializes a string with the [`FromStr`] implementation of `T`
+fn parsed<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: FromStr<Err = String>,
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value).map(Some).map_err(D::Error::custom)
+}
+
+/// Deserializes the `[crate_colors]` table of crate names and hex colors
+fn crate_colors<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(String, Color)>, D::Error> {
+    BTreeMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(crate_, hex)| {
+            let color = Srgba::hex(&hex).map_err(|_| {
+                D::Error::custom(format!("invalid color `{hex}` for crate `{crate_}`"))
+            })?;
+            Ok((crate_, color.into()))
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct ParseConfigError {
+    /// Line of the invalid key or value, if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ParseConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(
+                f,
+                "invalid debugdump config at line {line}: {}",
+                self.message
+            ),
+            None => write!(f, "invalid debugdump config: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseConfigError {}
+
+#[test]
+fn parse_config() {
+    let text = "# comment\n\
+        collapse_single_system_sets = true\n\
+        include = [\n    \"my_game::*\",\n    'other', # trailing comment\n]\n\
+        [style]\n\
+        preset = \"light\"\n\
+        rankdir = \"TD\"\n\
+        color_background = \"#ffffff\"\n\
+        penwidth_edge = 1.5\n\
+        [crate_colors]\n\
+        my_game = \"#ffcc00\"\n";
+    let config = SettingsConfig::parse(text).unwrap();
+    assert_eq!(config.collapse_single_system_sets, Some(true));
+    assert_eq!(config.include, ["my_game::*", "other"]);
+    assert_eq!(config.style.color_background.as_deref(), Some("#ffffff"));
+    assert_eq!(config.style.penwidth_edge, Some(1.5));
+    assert_eq!(config.crate_colors.len(), 1);
+
+    let settings = config.settings();
+    assert!(settings.collapse_single_system_sets);
+    assert_eq!(settings.style.color_background, "#ffffff");
+    assert_eq!(settings.style.color_set, Style::light().color_set);
+
+    let error = SettingsConfig::parse("[style]\nrankdir = \"up\"").unwrap_err();
+    assert_eq!(error.line, Some(2));
+    assert!(SettingsConfig::parse("colour = 1").is_err());
+}
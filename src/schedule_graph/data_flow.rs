@@ -0,0 +1,211 @@
+//! Data-flow graph of a schedule, showing which systems write and read each component and resource.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::{hash_map::HashMap, hash_set::HashSet};
+
+use crate::dot::DotGraph;
+
+use super::{analysis::SystemOrder, model::ScheduleModel, node_index_name, Settings};
+
+/// Data accessed by systems, ordered by kind and full type name for a stable output
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Data<'a> {
+    /// Systems that read or write all components or resources, e.g. exclusive systems
+    World,
+    Resource(&'a str),
+    Component(&'a str),
+}
+
+#[derive(Default)]
+struct DataAccess {
+    writers: Vec<NodeId>,
+    readers: Vec<NodeId>,
+}
+
+/// Formats the schedule model as a bipartite dot graph of systems and the components and resources they access.
+///
+/// Write edges go from a system into the data it writes, dashed read edges from the data into the systems reading it.
+/// Dotted edges order the systems touching the same data, leaving out orderings that follow from the others.
+/// Systems without any data access are left out.
+///
+/// The orderings are looked up in `unfiltered`, built with [`ScheduleModel::unfiltered`], so that systems and sets hidden by filters still order the others.
+pub fn schedule_model_data_flow_dot(
+    model: &ScheduleModel,
+    unfiltered: &ScheduleModel,
+    settings: &Settings,
+) -> String {
+    let mut dot = DotGraph::new(
+        "",
+        "digraph",
+        &[
+            ("splines", settings.style.edge_style.as_dot()),
+            ("rankdir", settings.style.schedule_rankdir.as_dot()),
+            ("bgcolor", &settings.style.color_background),
+            ("fontname", &settings.style.fontname),
+            ("nodesep", "0.15"),
+        ],
+    )
+    .edge_attributes(&[("penwidth", &format!("{}", settings.style.penwidth_edge))])
+    .node_attributes(&[("style", "filled"), ("fontname", &settings.style.fontname)]);
+
+    let mut data_access = BTreeMap::<Data, DataAccess>::new();
+    for system in &model.systems {
+        let access = &system.access;
+        let mut add = |data, write| {
+            let entry = data_access.entry(data).or_default();
+            if write {
+                entry.writers.push(system.id);
+            } else {
+                entry.readers.push(system.id);
+            }
+        };
+        if access.writes_all {
+            add(Data::World, true);
+        } else if access.reads_all {
+            add(Data::World, false);
+        }
+        for name in &access.resources_read {
+            add(Data::Resource(name), false);
+        }
+        for name in &access.resources_written {
+            add(Data::Resource(name), true);
+        }
+        for name in &access.components_read {
+            add(Data::Component(name), false);
+        }
+        for name in &access.components_written {
+            add(Data::Component(name), true);
+        }
+    }
+
+    for system in model
+        .systems
+        .iter()
+        .filter(|system| !system.access.is_empty())
+    {
+        let style = &system.style;
+        dot.add_node(
+            &node_index_name(system.id),
+            &[
+                ("shape", "box"),
+                ("label", &system.name),
+                ("tooltip", &system.full_name),
+                ("fillcolor", &style.bg_color),
+                ("fontcolor", &style.text_color),
+                ("color", &style.border_color),
+                ("penwidth", &style.border_width),
+            ],
+        );
+    }
+
+    for (i, (data, access)) in data_access.iter().enumerate() {
+        let data_id = format!("data_{i}");
+        let (shape, name) = match *data {
+            Data::World => ("doubleoctagon", "World"),
+            Data::Resource(name) => ("hexagon", name),
+            Data::Component(name) => ("ellipse", name),
+        };
+        let label = disqualified::ShortName(name).to_string();
+        dot.add_node(
+            &data_id,
+            &[
+                ("shape", shape),
+                ("label", &label),
+                ("tooltip", name),
+                ("fillcolor", &settings.style.color_set),
+                ("fontcolor", &settings.style.color_set_label),
+                ("color", &settings.style.color_set_border),
+            ],
+        );
+
+        let color = &settings.style.color_edge[i % settings.style.color_edge.len()];
+        for &writer in &access.writers {
+            let tooltip = format!("{} writes {label}", model.node_name(writer));
+            dot.add_edge(
+                &node_index_name(writer),
+                &data_id,
+                &[("color", color), ("tooltip", &tooltip)],
+            );
+        }
+        for &reader in &access.readers {
+            let tooltip = format!("{} reads {label}", model.node_name(reader));
+            dot.add_edge(
+                &data_id,
+                &node_index_name(reader),
+                &[("color", color), ("style", "dashed"), ("tooltip", &tooltip)],
+            );
+        }
+    }
+
+    let order = SystemOrder::new(unfiltered);
+    let mut runs_before = HashMap::<NodeId, HashSet<NodeId>>::default();
+    let mut ordered = HashSet::<(NodeId, NodeId)>::default();
+    for access in data_access.values() {
+        let mut systems: Vec<_> = access
+            .writers
+            .iter()
+            .chain(&access.readers)
+            .copied()
+            .collect();
+        for &system in &systems {
+            runs_before
+                .entry(system)
+                .or_insert_with(|| order.runs_before(system));
+        }
+        // a system runs before fewer systems than every system running before it, so this is a topological order
+        systems.sort_by_key(|system| std::cmp::Reverse(runs_before[system].len()));
+
+        for (i, &a) in systems.iter().enumerate() {
+            // systems running after an earlier successor of `a` are ordered through it
+            let mut implied = HashSet::<NodeId>::default();
+            for &b in &systems[i + 1..] {
+                if b == a || implied.contains(&b) || !runs_before[&a].contains(&b) {
+                    continue;
+                }
+                implied.extend(&runs_before[&b]);
+                if ordered.insert((a, b)) {
+                    let tooltip = format!("{} → {}", model.node_name(a), model.node_name(b));
+                    dot.add_edge(
+                        &node_index_name(a),
+                        &node_index_name(b),
+                        &[
+                            ("style", "dotted"),
+                            ("constraint", "false"),
+                            ("color", &settings.style.color_set_border),
+                            ("tooltip", &tooltip),
+                        ],
+                    );
+                }
+            }
+        }
+    }
+
+    dot.finish()
+}
+
+#[test]
+fn data_flow_order() {
+    use bevy_ecs::prelude::*;
+
+    #[derive(Resource)]
+    struct Score;
+
+    fn count(_: ResMut<Score>) {}
+    fn show(_: Res<Score>) {}
+    fn save(_: Res<Score>) {}
+    fn log(_: Res<Score>) {}
+
+    let (schedule, world) = super::model::test_schedule(((count, show, save).chain(), log));
+
+    let settings = Settings::default();
+    let model = ScheduleModel::new(&schedule, &world, &settings);
+    let dot = schedule_model_data_flow_dot(&model, &model, &settings);
+    assert!(dot.contains("count → show"));
+    assert!(dot.contains("show → save"));
+    // implied by the two above
+    assert!(!dot.contains("count → save"));
+    // `log` is unordered
+    assert_eq!(dot.matches(" → ").count(), 2);
+}
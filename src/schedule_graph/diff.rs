@@ -0,0 +1,606 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::hash_map::HashMap;
+
+use super::{
+    model::{ScheduleModel, SetKind},
+    Settings,
+};
+use crate::{dot::DotGraph, json::Json};
+
+const COLOR_ADDED: &str = "#2da44e";
+const COLOR_REMOVED: &str = "#cf222e";
+
+/// Whether an item of a [`ScheduleDiff`] only exists in the old or the new schedule, or in both
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Change {
+    Added,
+    Removed,
+    Unchanged,
+}
+
+/// Differences between two versions of a schedule.
+///
+/// Systems are matched by [`Settings::full_system_name`] and sets by [`Settings::system_set_name`],
+/// so both models should be built with the same settings. Multiple instances of the same system are matched
+/// in schedule order, and listed as `name #2` from the second one on.
+/// Dependencies on system types are attributed to the system itself.
+/// All lists are sorted by name, with sets before systems.
+pub struct ScheduleDiff {
+    pub old_label: String,
+    pub new_label: String,
+
+    pub systems_added: Vec<String>,
+    pub systems_removed: Vec<String>,
+    pub sets_added: Vec<String>,
+    pub sets_removed: Vec<String>,
+    /// `(set, child)` pairs of systems and sets that were put into or taken out of a set
+    pub membership_added: Vec<(String, String)>,
+    pub membership_removed: Vec<(String, String)>,
+    /// `(before, after)` pairs of explicit orderings
+    pub dependencies_added: Vec<(String, String)>,
+    pub dependencies_removed: Vec<(String, String)>,
+    /// Conflicting system pairs. Only filled when the models were built with [`Settings::ambiguity_enable`].
+    pub ambiguities_added: Vec<(String, String)>,
+    pub ambiguities_removed: Vec<(String, String)>,
+
+    /// Union of both schedules for [`ScheduleDiff::to_dot`]
+    nodes: BTreeMap<NodeKey, DiffNode>,
+    dependencies: Vec<(NodeKey, NodeKey, Change)>,
+    /// Added and removed ambiguities
+    ambiguities: Vec<(NodeKey, NodeKey, Change)>,
+}
+
+struct DiffNode {
+    label: String,
+    parent: Option<NodeKey>,
+    change: Change,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+enum NodeKind {
+    Set,
+    System,
+}
+
+/// Key a system or set is matched by: its name, and the occurrence of that name in schedule order
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct NodeKey {
+    kind: NodeKind,
+    name: String,
+    index: usize,
+}
+
+impl NodeKey {
+    /// Id that is unique across systems and sets, for the dot output
+    fn dot_id(&self) -> String {
+        format!("{:?}_{}_{}", self.kind, self.name, self.index)
+    }
+}
+
+impl std::fmt::Display for NodeKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.index {
+            0 => write!(f, "{}", self.name),
+            index => write!(f, "{} #{}", self.name, index + 1),
+        }
+    }
+}
+
+/// The parts of a model that are compared
+#[derive(Default)]
+struct Snapshot {
+    /// Key of every system and set of the model
+    keys: HashMap<NodeId, NodeKey>,
+    /// Display name of every system
+    systems: BTreeMap<NodeKey, String>,
+    sets: BTreeSet<NodeKey>,
+    /// First parent of every system and set
+    parent: BTreeMap<NodeKey, NodeKey>,
+    membership: BTreeSet<(NodeKey, NodeKey)>,
+    dependencies: BTreeSet<(NodeKey, NodeKey)>,
+    ambiguities: BTreeSet<(NodeKey, NodeKey)>,
+}
+
+impl Snapshot {
+    fn new(model: &ScheduleModel) -> Snapshot {
+        let mut snapshot = Snapshot::default();
+        let mut occurrences = HashMap::<(NodeKind, String), usize>::default();
+        let mut key = |kind, name: &str| {
+            let occurrence = occurrences.entry((kind, name.to_owned())).or_default();
+            let index = *occurrence;
+            *occurrence += 1;
+            NodeKey {
+                kind,
+                name: name.to_owned(),
+                index,
+            }
+        };
+        for system in &model.systems {
+            let system_key = key(NodeKind::System, &system.full_name);
+            snapshot
+                .systems
+                .insert(system_key.clone(), system.name.clone());
+            snapshot.keys.insert(system.id, system_key);
+        }
+        for set in &model.sets {
+            let set_key = key(NodeKind::Set, &set.name);
+            if set.kind == SetKind::Regular {
+                snapshot.sets.insert(set_key.clone());
+            }
+            snapshot.keys.insert(set.id, set_key);
+        }
+
+        for (parent, child) in model.hierarchy() {
+            let (parent, child) = (snapshot.key(model, parent), snapshot.key(model, child));
+            snapshot
+                .parent
+                .entry(child.clone())
+                .or_insert_with(|| parent.clone());
+            snapshot.membership.insert((parent, child));
+        }
+        for edge in &model.dependencies {
+            let (from, to) = (snapshot.key(model, edge.from), snapshot.key(model, edge.to));
+            if from != to {
+                snapshot.dependencies.insert((from, to));
+            }
+        }
+        for ambiguity in &model.ambiguities {
+            let a = snapshot.key(model, ambiguity.system_a);
+            let b = snapshot.key(model, ambiguity.system_b);
+            snapshot
+                .ambiguities
+                .insert(if a <= b { (a, b) } else { (b, a) });
+        }
+        snapshot
+    }
+
+    /// Key a node is matched by. System type sets are resolved to their system.
+    fn key(&self, model: &ScheduleModel, node_id: NodeId) -> NodeKey {
+        let node_id = match model.set(node_id).map(|set| set.kind) {
+            Some(SetKind::SystemType {
+                system: Some(system),
+            }) if model.contains(system) => system,
+            _ => node_id,
+        };
+        self.keys[&node_id].clone()
+    }
+}
+
+fn difference(a: &BTreeSet<NodeKey>, b: &BTreeSet<NodeKey>) -> Vec<String> {
+    a.difference(b).map(NodeKey::to_string).collect()
+}
+
+fn pair_difference(
+    a: &BTreeSet<(NodeKey, NodeKey)>,
+    b: &BTreeSet<(NodeKey, NodeKey)>,
+) -> Vec<(String, String)> {
+    a.difference(b)
+        .map(|(x, y)| (x.to_string(), y.to_string()))
+        .collect()
+}
+
+fn change_of<T: Ord>(item: &T, old: &BTreeSet<T>, new: &BTreeSet<T>) -> Change {
+    match (old.contains(item), new.contains(item)) {
+        (false, true) => Change::Added,
+        (true, false) => Change::Removed,
+        _ => Change::Unchanged,
+    }
+}
+
+impl ScheduleDiff {
+    pub fn new(old: &ScheduleModel, new: &ScheduleModel) -> ScheduleDiff {
+        let old_snapshot = Snapshot::new(old);
+        let new_snapshot = Snapshot::new(new);
+
+        let old_systems: BTreeSet<_> = old_snapshot.systems.keys().cloned().collect();
+        let new_systems: BTreeSet<_> = new_snapshot.systems.keys().cloned().collect();
+
+        let mut nodes = BTreeMap::new();
+        // prefer the placement in the new schedule for nodes in both
+        for snapshot in [&new_snapshot, &old_snapshot] {
+            for (key, label) in &snapshot.systems {
+                nodes.entry(key.clone()).or_insert_with(|| DiffNode {
+                    label: match key.index {
+                        0 => label.clone(),
+                        index => format!("{label} #{}", index + 1),
+                    },
+                    parent: snapshot.parent.get(key).cloned(),
+                    change: change_of(key, &old_systems, &new_systems),
+                });
+            }
+            for key in &snapshot.sets {
+                nodes.entry(key.clone()).or_insert_with(|| DiffNode {
+                    label: key.to_string(),
+                    parent: snapshot.parent.get(key).cloned(),
+                    change: change_of(key, &old_snapshot.sets, &new_snapshot.sets),
+                });
+            }
+        }
+
+        let dependencies = old_snapshot
+            .dependencies
+            .union(&new_snapshot.dependencies)
+            .map(|edge| {
+                let change =
+                    change_of(edge, &old_snapshot.dependencies, &new_snapshot.dependencies);
+                (edge.0.clone(), edge.1.clone(), change)
+            })
+            .collect();
+
+        let ambiguities = old_snapshot
+            .ambiguities
+            .symmetric_difference(&new_snapshot.ambiguities)
+            .map(|pair| {
+                let change = change_of(pair, &old_snapshot.ambiguities, &new_snapshot.ambiguities);
+                (pair.0.clone(), pair.1.clone(), change)
+            })
+            .collect();
+
+        ScheduleDiff {
+            old_label: old.label.clone(),
+            new_label: new.label.clone(),
+            systems_added: difference(&new_systems, &old_systems),
+            systems_removed: difference(&old_systems, &new_systems),
+            sets_added: difference(&new_snapshot.sets, &old_snapshot.sets),
+            sets_removed: difference(&old_snapshot.sets, &new_snapshot.sets),
+            membership_added: pair_difference(&new_snapshot.membership, &old_snapshot.membership),
+            membership_removed: pair_difference(&old_snapshot.membership, &new_snapshot.membership),
+            dependencies_added: pair_difference(
+                &new_snapshot.dependencies,
+                &old_snapshot.dependencies,
+            ),
+            dependencies_removed: pair_difference(
+                &old_snapshot.dependencies,
+                &new_snapshot.dependencies,
+            ),
+            ambiguities_added: pair_difference(
+                &new_snapshot.ambiguities,
+                &old_snapshot.ambiguities,
+            ),
+            ambiguities_removed: pair_difference(
+                &old_snapshot.ambiguities,
+                &new_snapshot.ambiguities,
+            ),
+            nodes,
+            dependencies,
+            ambiguities,
+        }
+    }
+
+    /// Whether the schedules are the same
+    pub fn is_empty(&self) -> bool {
+        self.systems_added.is_empty()
+            && self.systems_removed.is_empty()
+            && self.sets_added.is_empty()
+            && self.sets_removed.is_empty()
+            && self.membership_added.is_empty()
+            && self.membership_removed.is_empty()
+            && self.dependencies_added.is_empty()
+            && self.dependencies_removed.is_empty()
+            && self.ambiguities_added.is_empty()
+            && self.ambiguities_removed.is_empty()
+    }
+
+    /// Formats the differences as a human readable report, with `+` for added and `-` for removed items.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        if self.old_label == self.new_label {
+            let _ = writeln!(out, "Changes in {}", self.new_label);
+        } else {
+            let _ = writeln!(out, "Changes from {} to {}", self.old_label, self.new_label);
+        }
+        if self.is_empty() {
+            out.push_str("  no changes\n");
+            return out;
+        }
+
+        let mut section = |title: &str, added: Vec<String>, removed: Vec<String>| {
+            if added.is_empty() && removed.is_empty() {
+                return;
+            }
+            let _ = writeln!(out, "\n{title}");
+            for item in added {
+                let _ = writeln!(out, "  + {item}");
+            }
+            for item in removed {
+                let _ = writeln!(out, "  - {item}");
+            }
+        };
+        let pairs = |pairs: &[(String, String)], separator: &str| -> Vec<String> {
+            pairs
+                .iter()
+                .map(|(a, b)| format!("{a}{separator}{b}"))
+                .collect()
+        };
+
+        section(
+            "Systems",
+            self.systems_added.clone(),
+            self.systems_removed.clone(),
+        );
+        section("Sets", self.sets_added.clone(), self.sets_removed.clone());
+        section(
+            "Set membership",
+            pairs(&self.membership_added, " contains "),
+            pairs(&self.membership_removed, " contains "),
+        );
+        section(
+            "Ordering",
+            pairs(&self.dependencies_added, " -> "),
+            pairs(&self.dependencies_removed, " -> "),
+        );
+        section(
+            "Ambiguities",
+            pairs(&self.ambiguities_added, " <-> "),
+            pairs(&self.ambiguities_removed, " <-> "),
+        );
+
+        out
+    }
+
+    /// Formats the differences as JSON with `added` and `removed` lists for
+    /// `systems`, `sets`, `membership` (`{ set, child }`), `dependencies` (`{ before, after }`)
+    /// and `ambiguities` (`{ system_a, system_b }`).
+    pub fn to_json(&self) -> String {
+        let names = |names: &[String]| -> Json { names.iter().map(String::as_str).collect() };
+        let pairs = |pairs: &[(String, String)], [a, b]: [&'static str; 2]| -> Json {
+            pairs
+                .iter()
+                .map(|(x, y)| Json::Object(vec![(a, x.as_str().into()), (b, y.as_str().into())]))
+                .collect()
+        };
+        let change =
+            |added: Json, removed: Json| Json::Object(vec![("added", added), ("removed", removed)]);
+
+        Json::Object(vec![
+            ("old_schedule", self.old_label.as_str().into()),
+            ("new_schedule", self.new_label.as_str().into()),
+            (
+                "systems",
+                change(names(&self.systems_added), names(&self.systems_removed)),
+            ),
+            (
+                "sets",
+                change(names(&self.sets_added), names(&self.sets_removed)),
+            ),
+            (
+                "membership",
+                change(
+                    pairs(&self.membership_added, ["set", "child"]),
+                    pairs(&self.membership_removed, ["set", "child"]),
+                ),
+            ),
+            (
+                "dependencies",
+                change(
+                    pairs(&self.dependencies_added, ["before", "after"]),
+                    pairs(&self.dependencies_removed, ["before", "after"]),
+                ),
+            ),
+            (
+                "ambiguities",
+                change(
+                    pairs(&self.ambiguities_added, ["system_a", "system_b"]),
+                    pairs(&self.ambiguities_removed, ["system_a", "system_b"]),
+                ),
+            ),
+        ])
+        .to_string_pretty()
+    }
+
+    /// Renders both schedules in one dot graph, with added systems, sets and edges in green and removed ones in red.
+    ///
+    /// Systems and sets in multiple sets are only drawn in the first one.
+    pub fn to_dot(&self, settings: &Settings) -> String {
+        let style = &settings.style;
+        let mut dot = DotGraph::new(
+            "",
+            "digraph",
+            &[
+                ("compound", "true"),
+                ("splines", style.edge_style.as_dot()),
+                ("rankdir", style.schedule_rankdir.as_dot()),
+                ("bgcolor", &style.color_background),
+                ("fontname", &style.fontname),
+                ("nodesep", "0.15"),
+            ],
+        )
+        .edge_attributes(&[("penwidth", &format!("{}", style.penwidth_edge))])
+        .node_attributes(&[("shape", "box"), ("style", "filled")]);
+
+        let mut children = BTreeMap::<Option<&NodeKey>, Vec<&NodeKey>>::new();
+        for (key, node) in &self.nodes {
+            let parent = node
+                .parent
+                .as_ref()
+                .filter(|parent| self.nodes.contains_key(*parent));
+            children.entry(parent).or_default().push(key);
+        }
+        self.add_children(&mut dot, None, &children, settings);
+
+        // nodes that are only referenced by edges, like `ApplyDeferred`
+        let referenced: BTreeSet<_> = self
+            .dependencies
+            .iter()
+            .chain(&self.ambiguities)
+            .flat_map(|(from, to, _)| [from, to])
+            .filter(|key| !self.nodes.contains_key(*key))
+            .collect();
+        for key in referenced {
+            let label = key.to_string();
+            dot.add_node(
+                &diff_node_id(key),
+                &[
+                    ("label", &label),
+                    ("tooltip", &label),
+                    ("fillcolor", &style.color_set),
+                    ("fontcolor", &style.color_set_label),
+                ],
+            );
+        }
+
+        for (from, to, change) in &self.dependencies {
+            let color = match change {
+                Change::Added => COLOR_ADDED,
+                Change::Removed => COLOR_REMOVED,
+                Change::Unchanged => &style.color_set_border,
+            };
+            dot.add_edge(
+                &self.node_ref(from),
+                &self.node_ref(to),
+                &[
+                    ("lhead", &self.lref(to)),
+                    ("ltail", &self.lref(from)),
+                    ("tooltip", &format!("{from} → {to}")),
+                    ("color", color),
+                ],
+            );
+        }
+
+        for (a, b, change) in &self.ambiguities {
+            let color = match change {
+                Change::Removed => COLOR_REMOVED,
+                Change::Added | Change::Unchanged => COLOR_ADDED,
+            };
+            dot.add_edge(
+                &self.node_ref(a),
+                &self.node_ref(b),
+                &[
+                    ("dir", "none"),
+                    ("constraint", "false"),
+                    ("style", "dashed"),
+                    ("color", color),
+                    ("tooltip", &format!("{a} — {b}")),
+                ],
+            );
+        }
+
+        dot.finish()
+    }
+
+    fn add_children(
+        &self,
+        dot: &mut DotGraph,
+        parent: Option<&NodeKey>,
+        children: &BTreeMap<Option<&NodeKey>, Vec<&NodeKey>>,
+        settings: &Settings,
+    ) {
+        let style = &settings.style;
+        for &key in children.get(&parent).into_iter().flatten() {
+            let node = &self.nodes[key];
+            let color = match node.change {
+                Change::Added => Some(COLOR_ADDED),
+                Change::Removed => Some(COLOR_REMOVED),
+                Change::Unchanged => None,
+            };
+
+            if key.kind == NodeKind::Set {
+                let mut set_graph = DotGraph::subgraph(
+                    &key.dot_id(),
+                    &[
+                        ("style", "rounded,filled"),
+                        ("label", &node.label),
+                        ("tooltip", &node.label),
+                        ("fillcolor", &style.color_set),
+                        ("fontcolor", color.unwrap_or(&style.color_set_label)),
+                        ("color", color.unwrap_or(&style.color_set_border)),
+                        ("penwidth", "2"),
+                    ],
+                );
+                set_graph.add_invisible_node(&diff_marker_id(key));
+                self.add_children(&mut set_graph, Some(key), children, settings);
+                dot.add_sub_graph(set_graph);
+            } else {
+                dot.add_node(
+                    &diff_node_id(key),
+                    &[
+                        ("label", &node.label),
+                        ("tooltip", &key.to_string()),
+                        ("fillcolor", color.unwrap_or(&style.color_set)),
+                        ("fontcolor", &style.color_set_label),
+                        ("color", color.unwrap_or(&style.color_set_border)),
+                    ],
+                );
+            }
+        }
+    }
+
+    fn node_ref(&self, key: &NodeKey) -> String {
+        if self.is_drawn_set(key) {
+            diff_marker_id(key)
+        } else {
+            diff_node_id(key)
+        }
+    }
+
+    fn lref(&self, key: &NodeKey) -> String {
+        if self.is_drawn_set(key) {
+            format!("cluster{}", key.dot_id())
+        } else {
+            String::new()
+        }
+    }
+
+    /// Whether the key is a set drawn as a cluster, rather than a node only referenced by edges
+    fn is_drawn_set(&self, key: &NodeKey) -> bool {
+        key.kind == NodeKind::Set && self.nodes.contains_key(key)
+    }
+}
+
+fn diff_node_id(key: &NodeKey) -> String {
+    format!("node_{}", key.dot_id())
+}
+fn diff_marker_id(key: &NodeKey) -> String {
+    format!("set_marker_node_{}", key.dot_id())
+}
+
+#[test]
+fn diff_systems() {
+    use bevy_ecs::prelude::*;
+
+    fn a() {}
+    fn b() {}
+    fn c() {}
+
+    let model = |(schedule, world): (Schedule, World)| {
+        ScheduleModel::new(&schedule, &world, &Settings::default())
+    };
+    let short = |names: &[String]| -> Vec<String> {
+        names
+            .iter()
+            .map(|name| disqualified::ShortName(name).to_string())
+            .collect()
+    };
+    let short_pairs = |pairs: &[(String, String)]| -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(x, y)| {
+                let (x, y) = (disqualified::ShortName(x), disqualified::ShortName(y));
+                (x.to_string(), y.to_string())
+            })
+            .collect()
+    };
+
+    // two instances of `b`, `a` runs before them
+    let old = model(super::model::test_schedule((a.before(b), b, b)));
+    // one instance of `b` left, `c` added, and `b` now runs before `a`
+    let new = model(super::model::test_schedule((a, b.before(a), c)));
+
+    let diff = ScheduleDiff::new(&old, &new);
+    assert_eq!(short(&diff.systems_added), ["c"]);
+    assert_eq!(short(&diff.systems_removed), ["b #2"]);
+    assert_eq!(
+        short_pairs(&diff.dependencies_added),
+        [("b".to_owned(), "a".to_owned())]
+    );
+    assert_eq!(
+        short_pairs(&diff.dependencies_removed),
+        [("a".to_owned(), "b".to_owned())]
+    );
+    assert!(ScheduleDiff::new(&old, &old).is_empty());
+}
@@ -0,0 +1,163 @@
+//! Composable rules for which systems to include in the graph.
+//!
+//! ```rust
+//! use bevy_mod_debugdump::schedule_graph::{filter::Filter, Settings};
+//!
+//! // everything in `my_game` except `my_game::debug::*`
+//! let settings = Settings::default()
+//!     .include(Filter::system("my_game::*"))
+//!     .exclude(Filter::system("my_game::debug::*"));
+//!
+//! // systems in `PhysicsSet` or in `bevy_transform`
+//! let settings = Settings::default()
+//!     .include(Filter::in_set("PhysicsSet*").or(Filter::system("bevy_transform::*")));
+//! ```
+
+/// A rule matching systems by their full name or by the names of the sets they are in.
+///
+/// Patterns are globs, where `*` matches any number of characters (including `::`) and `?` matches one character.
+/// Regular expressions are not supported, use [`Settings::include_system`](super::Settings::include_system) with a closure instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// The full type name of the system matches the pattern
+    System(String),
+    /// The system is in a set whose name matches the pattern, directly or through nested sets.
+    /// Set names are formatted with [`Settings::system_set_name`](super::Settings::system_set_name).
+    InSet(String),
+    /// All of the filters match
+    All(Vec<Filter>),
+    /// Any of the filters match
+    Any(Vec<Filter>),
+    /// The filter doesn't match
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn system(pattern: impl Into<String>) -> Filter {
+        Filter::System(pattern.into())
+    }
+
+    pub fn in_set(pattern: impl Into<String>) -> Filter {
+        Filter::InSet(pattern.into())
+    }
+
+    /// Matches when both this filter and `other` match
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::All(mut filters) => {
+                filters.push(other);
+                Filter::All(filters)
+            }
+            filter => Filter::All(vec![filter, other]),
+        }
+    }
+
+    /// Matches when this filter or `other` matches
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Any(mut filters) => {
+                filters.push(other);
+                Filter::Any(filters)
+            }
+            filter => Filter::Any(vec![filter, other]),
+        }
+    }
+
+    /// Matches systems matching any of the `systems` globs or in a set matching any of the `sets` globs,
+    /// or `None` if there are no globs
+    #[cfg(feature = "config")]
+    pub(crate) fn any_of(systems: &[String], sets: &[String]) -> Option<Filter> {
+        let filters: Vec<_> = (systems.iter().map(Filter::system))
+            .chain(sets.iter().map(Filter::in_set))
+            .collect();
+        (!filters.is_empty()).then_some(Filter::Any(filters))
+    }
+
+    /// Whether the filter matches on set names, which then have to be collected for every system
+    pub(crate) fn uses_sets(&self) -> bool {
+        match self {
+            Filter::System(_) => false,
+            Filter::InSet(_) => true,
+            Filter::All(filters) | Filter::Any(filters) => filters.iter().any(Filter::uses_sets),
+            Filter::Not(filter) => filter.uses_sets(),
+        }
+    }
+
+    /// Matches the full name of a system and the names of all sets it is in
+    pub fn matches(&self, system_name: &str, set_names: &[String]) -> bool {
+        match self {
+            Filter::System(pattern) => glob_match(pattern, system_name),
+            Filter::InSet(pattern) => set_names.iter().any(|set| glob_match(pattern, set)),
+            Filter::All(filters) => filters
+                .iter()
+                .all(|filter| filter.matches(system_name, set_names)),
+            Filter::Any(filters) => filters
+                .iter()
+                .any(|filter| filter.matches(system_name, set_names)),
+            Filter::Not(filter) => !filter.matches(system_name, set_names),
+        }
+    }
+}
+
+impl std::ops::Not for Filter {
+    type Output = Filter;
+
+    /// Matches when this filter doesn't match
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
+
+/// Matches `text` against a glob `pattern` with `*` and `?` wildcards
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` in the pattern, and the text position it was tried at
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // let the last `*` match one more character
+                Some((star, star_t)) => {
+                    backtrack = Some((star, star_t + 1));
+                    p = star + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[test]
+fn filter_glob() {
+    assert!(glob_match("my_game::*", "my_game::debug::draw"));
+    assert!(glob_match("*::draw", "my_game::debug::draw"));
+    assert!(glob_match(
+        "my_?ame::*::draw*",
+        "my_game::debug::draw_gizmos"
+    ));
+    assert!(!glob_match("my_game::*", "other::my_game::draw"));
+    assert!(!glob_match("my_game", "my_game::draw"));
+
+    let filter = Filter::system("my_game::*").and(!Filter::system("my_game::debug::*"));
+    assert!(filter.matches("my_game::movement", &[]));
+    assert!(!filter.matches("my_game::debug::draw", &[]));
+    assert!(!filter.matches("bevy_ui::layout", &[]));
+
+    let filter = Filter::in_set("Physics*").or(Filter::system("bevy_transform::*"));
+    assert!(filter.matches("my_game::collide", &["PhysicsSet::Step".into()]));
+    assert!(filter.matches("bevy_transform::propagate", &[]));
+    assert!(!filter.matches("my_game::collide", &["Render".into()]));
+}
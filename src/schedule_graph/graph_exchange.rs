@@ -0,0 +1,298 @@
+//! GraphML and GEXF output for graph analysis tools like yEd, Gephi or networkx.
+//!
+//! Both formats contain the same flat graph: every system and set is a node, and
+//! `hierarchy`, `dependency` and `ambiguity` relations are edges distinguished by their `kind` attribute.
+
+use std::fmt::Write;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::hash_map::HashMap;
+
+use super::model::{ScheduleModel, SetKind};
+use crate::dot::html_escape;
+
+/// Attribute value of a node or edge
+enum Value {
+    String(String),
+    Bool(bool),
+    Int(usize),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(value) => f.write_str(&html_escape(value)),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Int(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Name, and type as spelled by both GraphML and GEXF
+const NODE_ATTRIBUTES: [(&str, &str); 7] = [
+    ("kind", "string"),
+    ("full_name", "string"),
+    ("crate", "string"),
+    ("sets", "string"),
+    ("exclusive", "boolean"),
+    ("conflicts", "int"),
+    ("run_conditions", "int"),
+];
+const EDGE_ATTRIBUTES: [(&str, &str); 2] = [("kind", "string"), ("transitive", "boolean")];
+
+struct Node {
+    id: String,
+    label: String,
+    attributes: [Value; NODE_ATTRIBUTES.len()],
+}
+
+struct Edge {
+    source: String,
+    target: String,
+    directed: bool,
+    attributes: [Value; EDGE_ATTRIBUTES.len()],
+}
+
+/// Formats the schedule model as GraphML.
+///
+/// Nodes have the attributes
+/// - `name`
+/// - `kind`: `"system"`, `"set"`, `"system_type"` or `"apply_deferred"`
+/// - `full_name`
+/// - `crate`: the first path segment of the full system name, empty for sets
+/// - `sets`: names of the parent sets, separated by `;`
+/// - `exclusive`: whether the system takes `&mut World`
+/// - `conflicts`: number of ambiguities the system is part of
+/// - `run_conditions`: number of run conditions
+///
+/// Edges have a `kind` of `"hierarchy"` (set to child), `"dependency"` (runs before) or `"ambiguity"` (undirected),
+/// and dependency edges have a `transitive` flag, see [`ScheduleModel::with_transitive_flags`].
+pub fn schedule_model_graphml(model: &ScheduleModel) -> String {
+    let (nodes, edges) = collect(model);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n");
+    for (name, ty) in NODE_ATTRIBUTES {
+        let _ = writeln!(
+            out,
+            "  <key id=\"{name}\" for=\"node\" attr.name=\"{name}\" attr.type=\"{ty}\"/>"
+        );
+    }
+    for (name, ty) in EDGE_ATTRIBUTES {
+        let _ = writeln!(
+            out,
+            "  <key id=\"edge_{name}\" for=\"edge\" attr.name=\"{name}\" attr.type=\"{ty}\"/>"
+        );
+    }
+    let _ = writeln!(
+        out,
+        "  <graph id=\"{}\" edgedefault=\"directed\">",
+        html_escape(&model.label)
+    );
+
+    for node in &nodes {
+        let _ = writeln!(out, "    <node id=\"{}\">", node.id);
+        let _ = writeln!(
+            out,
+            "      <data key=\"name\">{}</data>",
+            html_escape(&node.label)
+        );
+        for ((name, _), value) in NODE_ATTRIBUTES.iter().zip(&node.attributes) {
+            let _ = writeln!(out, "      <data key=\"{name}\">{value}</data>");
+        }
+        out.push_str("    </node>\n");
+    }
+    for edge in &edges {
+        let _ = writeln!(
+            out,
+            "    <edge source=\"{}\" target=\"{}\" directed=\"{}\">",
+            edge.source, edge.target, edge.directed
+        );
+        for ((name, _), value) in EDGE_ATTRIBUTES.iter().zip(&edge.attributes) {
+            let _ = writeln!(out, "      <data key=\"edge_{name}\">{value}</data>");
+        }
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+/// Formats the schedule model as GEXF 1.3, with the same attributes as [`schedule_model_graphml`].
+pub fn schedule_model_gexf(model: &ScheduleModel) -> String {
+    let (nodes, edges) = collect(model);
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+    let _ = writeln!(
+        out,
+        "  <meta><description>{}</description></meta>",
+        html_escape(&model.label)
+    );
+    out.push_str("  <graph defaultedgetype=\"directed\" mode=\"static\">\n");
+    for (class, attributes) in [
+        ("node", &NODE_ATTRIBUTES[..]),
+        ("edge", &EDGE_ATTRIBUTES[..]),
+    ] {
+        let _ = writeln!(out, "    <attributes class=\"{class}\">");
+        for (i, (name, ty)) in attributes.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <attribute id=\"{i}\" title=\"{name}\" type=\"{ty}\"/>"
+            );
+        }
+        out.push_str("    </attributes>\n");
+    }
+
+    out.push_str("    <nodes>\n");
+    for node in &nodes {
+        let _ = writeln!(
+            out,
+            "      <node id=\"{}\" label=\"{}\">",
+            node.id,
+            html_escape(&node.label)
+        );
+        write_gexf_attvalues(&mut out, &node.attributes);
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n");
+
+    out.push_str("    <edges>\n");
+    for (i, edge) in edges.iter().enumerate() {
+        let kind = if edge.directed {
+            "directed"
+        } else {
+            "undirected"
+        };
+        let _ = writeln!(
+            out,
+            "      <edge id=\"{i}\" source=\"{}\" target=\"{}\" type=\"{kind}\">",
+            edge.source, edge.target
+        );
+        write_gexf_attvalues(&mut out, &edge.attributes);
+        out.push_str("      </edge>\n");
+    }
+    out.push_str("    </edges>\n");
+
+    out.push_str("  </graph>\n");
+    out.push_str("</gexf>\n");
+    out
+}
+
+fn write_gexf_attvalues(out: &mut String, values: &[Value]) {
+    out.push_str("        <attvalues>\n");
+    for (i, value) in values.iter().enumerate() {
+        let _ = writeln!(out, "          <attvalue for=\"{i}\" value=\"{value}\"/>");
+    }
+    out.push_str("        </attvalues>\n");
+}
+
+fn collect(model: &ScheduleModel) -> (Vec<Node>, Vec<Edge>) {
+    let mut ids = HashMap::<NodeId, String>::default();
+    for (i, system) in model.systems.iter().enumerate() {
+        ids.insert(system.id, format!("system{i}"));
+    }
+    for (i, set) in model.sets.iter().enumerate() {
+        ids.insert(set.id, format!("set{i}"));
+    }
+
+    let mut conflicts = HashMap::<NodeId, usize>::default();
+    for ambiguity in &model.ambiguities {
+        *conflicts.entry(ambiguity.system_a).or_default() += 1;
+        *conflicts.entry(ambiguity.system_b).or_default() += 1;
+    }
+
+    let set_names = |parents: &[NodeId]| {
+        let names: Vec<_> = parents
+            .iter()
+            .map(|&parent| model.node_name(parent))
+            .collect();
+        Value::String(names.join(";"))
+    };
+
+    let systems = model.systems.iter().map(|system| Node {
+        id: ids[&system.id].clone(),
+        label: system.name.clone(),
+        attributes: [
+            Value::String("system".to_owned()),
+            Value::String(system.full_name.clone()),
+            Value::String(crate_name(&system.full_name).to_owned()),
+            set_names(&system.parents),
+            Value::Bool(system.exclusive),
+            Value::Int(conflicts.get(&system.id).copied().unwrap_or(0)),
+            Value::Int(system.conditions.len()),
+        ],
+    });
+    let sets = model.sets.iter().map(|set| {
+        let kind = match set.kind {
+            SetKind::Regular => "set",
+            SetKind::SystemType { .. } => "system_type",
+            SetKind::ApplyDeferred => "apply_deferred",
+        };
+        Node {
+            id: ids[&set.id].clone(),
+            label: set.name.clone(),
+            attributes: [
+                Value::String(kind.to_owned()),
+                Value::String(set.name.clone()),
+                Value::String(String::new()),
+                set_names(&set.parents),
+                Value::Bool(false),
+                Value::Int(0),
+                Value::Int(set.conditions.len()),
+            ],
+        }
+    });
+    let nodes = systems.chain(sets).collect();
+
+    let edge = |from: NodeId, to: NodeId, directed: bool, kind: &str, transitive: bool| Edge {
+        source: ids[&from].clone(),
+        target: ids[&to].clone(),
+        directed,
+        attributes: [Value::String(kind.to_owned()), Value::Bool(transitive)],
+    };
+    let hierarchy = model
+        .hierarchy()
+        .map(|(parent, child)| edge(parent, child, true, "hierarchy", false));
+    let dependencies = model.dependencies.iter().map(|dependency| {
+        edge(
+            dependency.from,
+            dependency.to,
+            true,
+            "dependency",
+            dependency.transitive,
+        )
+    });
+    let ambiguities = model.ambiguities.iter().map(|ambiguity| {
+        edge(
+            ambiguity.system_a,
+            ambiguity.system_b,
+            false,
+            "ambiguity",
+            false,
+        )
+    });
+    let edges = hierarchy.chain(dependencies).chain(ambiguities).collect();
+
+    (nodes, edges)
+}
+
+/// First path segment of a type name, e.g. `bevy_render` for `bevy_render::view::prepare_view_targets`
+pub(super) fn crate_name(full_name: &str) -> &str {
+    let name = full_name.trim_start_matches('<');
+    name.split_once("::").map_or("", |(krate, _)| krate)
+}
+
+#[test]
+fn crate_name_of_systems() {
+    assert_eq!(
+        crate_name("bevy_render::view::prepare_view_targets"),
+        "bevy_render"
+    );
+    assert_eq!(crate_name("<my_game::Foo as Bar>::baz"), "my_game");
+    assert_eq!(crate_name("apply_deferred"), "");
+}
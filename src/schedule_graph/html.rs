@@ -0,0 +1,19 @@
+use super::{model::ScheduleModel, svg::schedule_model_svg, Settings};
+use crate::dot::html_escape;
+
+/// Renders the schedule model as a single self-contained HTML page.
+///
+/// The page embeds the output of [`schedule_model_svg`] and a small script without external dependencies,
+/// which adds pan and zoom, collapsible system sets, search by system name,
+/// and highlighting of everything running before or after a clicked system.
+pub fn schedule_model_html(model: &ScheduleModel, settings: &Settings) -> String {
+    let svg = schedule_model_svg(model, settings);
+
+    include_str!("viewer.html")
+        .replace("{{title}}", &html_escape(&model.label))
+        .replace(
+            "{{background}}",
+            &html_escape(&settings.style.color_background),
+        )
+        .replace("{{svg}}", &svg)
+}
@@ -0,0 +1,127 @@
+use bevy_ecs::schedule::NodeId;
+
+use super::model::{ScheduleModel, SetKind};
+use crate::json::Json;
+
+/// Version of the JSON schema written by [`schedule_model_json`].
+/// Incremented whenever a field is removed or changes its meaning.
+pub const SCHEMA_VERSION: usize = 1;
+
+/// Formats the schedule model as JSON.
+///
+/// The output is an object with the following fields:
+///
+/// - `schema_version`: [`SCHEMA_VERSION`]
+/// - `schedule`: `Debug` name of the schedule label
+/// - `systems`: `{ id, name, full_name, exclusive, sets, run_conditions }`, where `sets` are the ids of the parent sets
+/// - `sets`: `{ id, name, kind, parents, run_conditions }`, where `kind` is one of
+///   `"set"`, `"system_type"` or `"apply_deferred"`
+/// - `hierarchy`: `{ parent, child }` edges
+/// - `dependencies`: `{ from, to, transitive }` edges, where `from` runs before `to`.
+///   Transitive edges are kept, and flagged if the model was built with
+///   [`ScheduleModel::with_transitive_flags`] like [`schedule_graph_json`](super::schedule_graph_json) does.
+/// - `ambiguities`: `{ system_a, system_b, world, conflicts }`, where `conflicts` are `{ name, short_name }`
+///   of the conflicting components and resources, and `world` is set if the systems conflict on the whole world
+///
+/// Ids are opaque strings, unique within one schedule. Use the names to match across runs.
+pub fn schedule_model_json(model: &ScheduleModel) -> String {
+    let systems = model
+        .systems
+        .iter()
+        .map(|system| {
+            Json::Object(vec![
+                ("id", node_id(system.id)),
+                ("name", system.name.as_str().into()),
+                ("full_name", system.full_name.as_str().into()),
+                ("exclusive", system.exclusive.into()),
+                (
+                    "sets",
+                    system.parents.iter().map(|&id| node_id(id)).collect(),
+                ),
+                ("run_conditions", system.conditions.len().into()),
+            ])
+        })
+        .collect();
+
+    let sets = model
+        .sets
+        .iter()
+        .map(|set| {
+            let kind = match set.kind {
+                SetKind::Regular => "set",
+                SetKind::SystemType { .. } => "system_type",
+                SetKind::ApplyDeferred => "apply_deferred",
+            };
+            Json::Object(vec![
+                ("id", node_id(set.id)),
+                ("name", set.name.as_str().into()),
+                ("kind", kind.into()),
+                (
+                    "parents",
+                    set.parents.iter().map(|&id| node_id(id)).collect(),
+                ),
+                ("run_conditions", set.conditions.len().into()),
+            ])
+        })
+        .collect();
+
+    let hierarchy = model
+        .hierarchy()
+        .map(|(parent, child)| {
+            Json::Object(vec![("parent", node_id(parent)), ("child", node_id(child))])
+        })
+        .collect();
+
+    let dependencies = model
+        .dependencies
+        .iter()
+        .map(|edge| {
+            Json::Object(vec![
+                ("from", node_id(edge.from)),
+                ("to", node_id(edge.to)),
+                ("transitive", edge.transitive.into()),
+            ])
+        })
+        .collect();
+
+    let ambiguities = model
+        .ambiguities
+        .iter()
+        .map(|ambiguity| {
+            let conflicts = ambiguity
+                .conflicts
+                .iter()
+                .map(|name| {
+                    Json::Object(vec![
+                        ("name", name.as_str().into()),
+                        (
+                            "short_name",
+                            disqualified::ShortName(name).to_string().into(),
+                        ),
+                    ])
+                })
+                .collect();
+            Json::Object(vec![
+                ("system_a", node_id(ambiguity.system_a)),
+                ("system_b", node_id(ambiguity.system_b)),
+                ("world", ambiguity.conflicts.is_empty().into()),
+                ("conflicts", conflicts),
+            ])
+        })
+        .collect();
+
+    Json::Object(vec![
+        ("schema_version", SCHEMA_VERSION.into()),
+        ("schedule", model.label.as_str().into()),
+        ("systems", systems),
+        ("sets", sets),
+        ("hierarchy", hierarchy),
+        ("dependencies", dependencies),
+        ("ambiguities", ambiguities),
+    ])
+    .to_string_pretty()
+}
+
+fn node_id(id: NodeId) -> Json {
+    Json::String(format!("{id:?}"))
+}
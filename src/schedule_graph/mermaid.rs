@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::{hash_map::HashMap, hash_set::HashSet};
+
+use super::{
+    model::{ScheduleModel, SetKind},
+    Settings,
+};
+
+/// Formats the schedule model as a mermaid flowchart.
+///
+/// System sets become `subgraph` blocks, dependencies become arrows and ambiguities become dotted links.
+pub fn schedule_model_mermaid(model: &ScheduleModel, settings: &Settings) -> String {
+    let mut context = MermaidContext {
+        settings,
+        model,
+        ids: HashMap::default(),
+        buffer: String::new(),
+        link_count: 0,
+        color_edge_idx: 0,
+    };
+    for (i, system) in model.systems.iter().enumerate() {
+        context.ids.insert(system.id, format!("system{i}"));
+    }
+    for (i, set) in model.sets.iter().enumerate() {
+        context.ids.insert(set.id, format!("set{i}"));
+    }
+
+    // rankdir values happen to be the same in dot and mermaid
+    let _ = writeln!(
+        context.buffer,
+        "flowchart {}",
+        settings.style.schedule_rankdir.as_dot()
+    );
+
+    context.add_sets();
+    context.add_freestanding_systems();
+    context.add_system_type_sets();
+    context.add_dependencies();
+    if settings.ambiguity_enable {
+        context.add_ambiguities();
+    }
+
+    context.buffer
+}
+
+#[test]
+fn escape_correctly() {
+    assert_eq!(escape_label("a"), "\"a\"");
+    assert_eq!(escape_label("say \"hi\""), "\"say #quot;hi#quot;\"");
+    assert_eq!(
+        escape_label("Events<A>::update #1"),
+        "\"Events#lt;A#gt;::update #35;1\""
+    );
+}
+
+fn escape_label(input: &str) -> String {
+    format!("\"{}\"", escape_text(input))
+}
+
+fn escape_text(input: &str) -> String {
+    input
+        .replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+struct MermaidContext<'a> {
+    settings: &'a Settings,
+    model: &'a ScheduleModel,
+
+    ids: HashMap<NodeId, String>,
+    buffer: String,
+    link_count: usize,
+    color_edge_idx: usize,
+}
+
+impl MermaidContext<'_> {
+    /// Add sets with systems recursively, as well as sets belonging to multiple sets without a common ancestor
+    fn add_sets(&mut self) {
+        for &set_id in &self.model.sets_freestanding {
+            self.add_set(set_id, 1);
+        }
+        for &set_id in self.model.sets_in_multiple_sets(None) {
+            self.add_set_in_multiple_sets(set_id, 1);
+        }
+    }
+
+    /// Add freestanding systems that do not belong to a set, as well as systems in multiple sets without a common ancestor
+    fn add_freestanding_systems(&mut self) {
+        for &system_id in &self.model.systems_freestanding {
+            self.add_system(system_id, 1);
+        }
+        for &system_id in self.model.systems_in_multiple_sets(None) {
+            self.add_system_in_multiple_sets(system_id, 1);
+        }
+    }
+
+    /// Add nodes for system type sets that don't resolve to an included system, if a dependency uses them
+    fn add_system_type_sets(&mut self) {
+        let model = self.model;
+        let used: HashSet<NodeId> = model
+            .drawn_dependencies()
+            .flat_map(|edge| [edge.from, edge.to])
+            .collect();
+        for set in model.sets.iter().filter(|set| used.contains(&set.id)) {
+            let label = match set.kind {
+                SetKind::Regular => continue,
+                SetKind::SystemType {
+                    system: Some(system),
+                } if model.contains(system) => continue,
+                SetKind::SystemType { system: Some(_) } => set.name.clone(),
+                SetKind::ApplyDeferred => "ApplyDeferred".to_owned(),
+                SetKind::SystemType { system: None } => format!("<missing> {}", set.name),
+            };
+            let _ = writeln!(
+                self.buffer,
+                "    {}[{}]",
+                self.ids[&set.id],
+                escape_label(&label)
+            );
+        }
+    }
+
+    fn add_dependencies(&mut self) {
+        let model = self.model;
+        for edge in model.drawn_dependencies() {
+            let line = format!(
+                "    {} --> {}",
+                self.node_ref(edge.from),
+                self.node_ref(edge.to)
+            );
+            self.buffer.push_str(&line);
+            self.buffer.push('\n');
+            let color = self.next_edge_color();
+            self.style_link(color);
+        }
+    }
+
+    fn add_ambiguities(&mut self) {
+        let (model, settings) = (self.model, self.settings);
+        for ambiguity in &model.ambiguities {
+            let label = if ambiguity.conflicts.is_empty() {
+                "World".to_owned()
+            } else {
+                ambiguity
+                    .conflicts
+                    .iter()
+                    .map(|name| disqualified::ShortName(name).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let line = format!(
+                "    {} -.-|{}| {}",
+                self.node_ref(ambiguity.system_a),
+                escape_label(&label),
+                self.node_ref(ambiguity.system_b),
+            );
+            self.buffer.push_str(&line);
+            self.buffer.push('\n');
+            self.style_link(&settings.style.ambiguity_color);
+        }
+    }
+}
+
+impl<'a> MermaidContext<'a> {
+    fn add_set(&mut self, set_id: NodeId, depth: usize) {
+        let indent = "    ".repeat(depth);
+        let id = self.ids[&set_id].clone();
+        let name = &self.model.set(set_id).unwrap().name;
+
+        if self.model.collapsed_sets.contains(&set_id) {
+            let _ = writeln!(self.buffer, "{indent}{id}[{}]", escape_label(name));
+            self.style_set(&id, &indent);
+            return;
+        }
+
+        let _ = writeln!(self.buffer, "{indent}subgraph {id}[{}]", escape_label(name));
+        let _ = writeln!(
+            self.buffer,
+            "{indent}    direction {}",
+            self.settings.style.schedule_rankdir.as_dot()
+        );
+
+        for &nested_set_id in self.model.sets_in_single_set(set_id) {
+            self.add_set(nested_set_id, depth + 1);
+        }
+        for &nested_set_id in self.model.sets_in_multiple_sets(Some(set_id)) {
+            self.add_set_in_multiple_sets(nested_set_id, depth + 1);
+        }
+        for &system_id in self.model.systems_in_single_set(set_id) {
+            self.add_system(system_id, depth + 1);
+        }
+        for &system_id in self.model.systems_in_multiple_sets(Some(set_id)) {
+            self.add_system_in_multiple_sets(system_id, depth + 1);
+        }
+
+        let _ = writeln!(self.buffer, "{indent}end");
+        self.style_set(&id, &indent);
+    }
+
+    fn style_set(&mut self, id: &str, indent: &str) {
+        let _ = writeln!(
+            self.buffer,
+            "{indent}style {id} fill:{},stroke:{},color:{}",
+            self.settings.style.color_set,
+            self.settings.style.color_set_border,
+            self.settings.style.color_set_label,
+        );
+    }
+
+    fn add_set_in_multiple_sets(&mut self, set_id: NodeId, depth: usize) {
+        let settings = self.settings;
+        self.add_set(set_id, depth);
+
+        for &parent in &self.model.set(set_id).unwrap().parents {
+            let _ = writeln!(
+                self.buffer,
+                "{}{} -.- {}",
+                "    ".repeat(depth),
+                self.ids[&parent],
+                self.ids[&set_id]
+            );
+            self.style_link(&settings.style.multiple_set_edge_color);
+        }
+    }
+
+    fn add_system(&mut self, system_id: NodeId, depth: usize) {
+        let system = self.model.system(system_id).unwrap();
+        self.write_system_node(system_id, escape_label(&system.name), depth);
+    }
+
+    fn add_system_in_multiple_sets(&mut self, system_id: NodeId, depth: usize) {
+        let settings = self.settings;
+        let system = self.model.system(system_id).unwrap();
+        let parents: Vec<_> = system
+            .parents
+            .iter()
+            .map(|&parent| self.model.node_name(parent))
+            .collect();
+        let label = format!(
+            "\"{}<br>In multiple sets, {}\"",
+            escape_text(&system.name),
+            escape_text(&parents.join(", "))
+        );
+        self.write_system_node(system_id, label, depth);
+
+        for &parent in &system.parents {
+            let _ = writeln!(
+                self.buffer,
+                "{}{} -.- {}",
+                "    ".repeat(depth),
+                self.ids[&system_id],
+                self.ids[&parent]
+            );
+            self.style_link(&settings.style.multiple_set_edge_color);
+        }
+    }
+
+    fn write_system_node(&mut self, system_id: NodeId, label: String, depth: usize) {
+        // systems in collapsed sets are drawn as their set
+        if self.model.collapsed_set_children.contains_key(&system_id) {
+            return;
+        }
+
+        let style = &self.model.system(system_id).unwrap().style;
+        let id = &self.ids[&system_id];
+        let indent = "    ".repeat(depth);
+        let _ = writeln!(self.buffer, "{indent}{id}[{label}]");
+        let _ = writeln!(
+            self.buffer,
+            "{indent}style {id} fill:{},stroke:{},stroke-width:{}px,color:{}",
+            style.bg_color, style.border_color, style.border_width, style.text_color,
+        );
+    }
+
+    fn style_link(&mut self, color: &str) {
+        let _ = writeln!(
+            self.buffer,
+            "    linkStyle {} stroke:{color},stroke-width:{}px",
+            self.link_count, self.settings.style.penwidth_edge
+        );
+        self.link_count += 1;
+    }
+
+    fn node_ref(&self, node_id: NodeId) -> &str {
+        let node_id = match self.model.set(node_id).map(|set| set.kind) {
+            Some(SetKind::SystemType {
+                system: Some(system),
+            }) if self.model.contains(system) => system,
+            _ => node_id,
+        };
+        let node_id = self
+            .model
+            .collapsed_set_children
+            .get(&node_id)
+            .copied()
+            .unwrap_or(node_id);
+
+        &self.ids[&node_id]
+    }
+
+    fn next_edge_color(&mut self) -> &'a str {
+        let idx = self.color_edge_idx;
+        self.color_edge_idx = (idx + 1) % self.settings.style.color_edge.len();
+        &self.settings.style.color_edge[idx]
+    }
+}
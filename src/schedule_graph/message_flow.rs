@@ -0,0 +1,311 @@
+//! Which systems write and read each message type, and whether readers see the messages in the same frame.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::{hash_map::HashMap, hash_set::HashSet};
+
+use crate::dot::DotGraph;
+
+use super::{
+    analysis::SystemOrder, model::ScheduleModel, node_index_name, settings::NodeStyle, Settings,
+};
+
+/// When a reader sees the messages written by a writer of the same schedule
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageTiming {
+    /// The reader runs after the writer, in the same frame
+    SameFrame,
+    /// The reader runs before the writer, so it reads the messages one frame late
+    NextFrame,
+    /// Neither is ordered, so whether the reader sees the messages in the same or the next frame depends on the executor
+    Unordered,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MessageEdge {
+    pub writer: NodeId,
+    pub reader: NodeId,
+    pub timing: MessageTiming,
+}
+
+/// The systems writing and reading one message type
+pub struct MessageChannel {
+    /// Full type name of the message
+    pub message: String,
+    pub writers: Vec<NodeId>,
+    pub readers: Vec<NodeId>,
+    /// Every pair of a writer and a different reader
+    pub edges: Vec<MessageEdge>,
+}
+
+struct SystemLabel {
+    name: String,
+    full_name: String,
+    style: NodeStyle,
+}
+
+/// Message flow between the systems of a schedule.
+///
+/// Systems writing all resources, like exclusive systems, are left out since their messages aren't known.
+/// Messages written or read in other schedules only show up with their writers or readers in this one.
+pub struct MessageFlow {
+    pub label: String,
+    /// Sorted by the message type name
+    pub channels: Vec<MessageChannel>,
+
+    systems: HashMap<NodeId, SystemLabel>,
+}
+
+impl MessageFlow {
+    /// Collects the message types of the systems of `model`. Whether a reader runs after a writer is looked up in `unfiltered`,
+    /// built with [`ScheduleModel::unfiltered`], so that systems and sets hidden by filters still order them.
+    pub fn new(model: &ScheduleModel, unfiltered: &ScheduleModel) -> MessageFlow {
+        let mut channels = BTreeMap::<&str, MessageChannel>::new();
+        for system in &model.systems {
+            let access = &system.access;
+            let written = access.resources_written.iter().map(|name| (name, true));
+            let read = access.resources_read.iter().map(|name| (name, false));
+            for (resource, write) in written.chain(read) {
+                let Some(message) = message_type(resource) else {
+                    continue;
+                };
+                let channel = channels.entry(message).or_insert_with(|| MessageChannel {
+                    message: message.to_owned(),
+                    writers: Vec::new(),
+                    readers: Vec::new(),
+                    edges: Vec::new(),
+                });
+                if write {
+                    channel.writers.push(system.id);
+                } else {
+                    channel.readers.push(system.id);
+                }
+            }
+        }
+
+        let order = SystemOrder::new(unfiltered);
+        let mut runs_before = HashMap::<NodeId, HashSet<NodeId>>::default();
+        for channel in channels.values() {
+            for &system in channel.writers.iter().chain(&channel.readers) {
+                runs_before
+                    .entry(system)
+                    .or_insert_with(|| order.runs_before(system));
+            }
+        }
+        for channel in channels.values_mut() {
+            for &writer in &channel.writers {
+                for &reader in &channel.readers {
+                    // a system reading its own messages, e.g. with `ResMut<Messages<M>>`, isn't a flow between systems
+                    if writer == reader {
+                        continue;
+                    }
+                    let timing = if runs_before[&writer].contains(&reader) {
+                        MessageTiming::SameFrame
+                    } else if runs_before[&reader].contains(&writer) {
+                        MessageTiming::NextFrame
+                    } else {
+                        MessageTiming::Unordered
+                    };
+                    channel.edges.push(MessageEdge {
+                        writer,
+                        reader,
+                        timing,
+                    });
+                }
+            }
+        }
+
+        let channels: Vec<_> = channels.into_values().collect();
+        let systems = channels
+            .iter()
+            .flat_map(|channel| channel.writers.iter().chain(&channel.readers))
+            .map(|&id| {
+                let system = model.system(id).unwrap();
+                let label = SystemLabel {
+                    name: system.name.clone(),
+                    full_name: system.full_name.clone(),
+                    style: system.style.clone(),
+                };
+                (id, label)
+            })
+            .collect();
+
+        MessageFlow {
+            label: model.label.clone(),
+            channels,
+            systems,
+        }
+    }
+
+    /// Readers that see messages of a writer one frame late, or depending on the executor
+    pub fn delayed_edges(&self) -> impl Iterator<Item = (&MessageChannel, &MessageEdge)> {
+        self.channels.iter().flat_map(|channel| {
+            channel
+                .edges
+                .iter()
+                .filter(|edge| edge.timing != MessageTiming::SameFrame)
+                .map(move |edge| (channel, edge))
+        })
+    }
+
+    /// Lists the writers and readers of every message type and when each reader sees the messages
+    pub fn to_text(&self) -> String {
+        let name = |id: NodeId| self.systems[&id].name.as_str();
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{}: {} message types, {} readers that may see messages a frame late",
+            self.label,
+            self.channels.len(),
+            self.delayed_edges().count()
+        );
+        for channel in &self.channels {
+            let _ = writeln!(out, "\n{}", disqualified::ShortName(&channel.message));
+            if channel.writers.is_empty() {
+                let _ = writeln!(out, "  not written in this schedule");
+            }
+            if channel.readers.is_empty() {
+                let _ = writeln!(out, "  not read in this schedule");
+            }
+            for edge in &channel.edges {
+                let timing = match edge.timing {
+                    MessageTiming::SameFrame => "same frame",
+                    MessageTiming::NextFrame => "next frame, the reader runs first",
+                    MessageTiming::Unordered => "unordered, same or next frame",
+                };
+                let _ = writeln!(
+                    out,
+                    "  {} → {}: {timing}",
+                    name(edge.writer),
+                    name(edge.reader)
+                );
+            }
+        }
+        out
+    }
+
+    /// Draws an edge from every writer to every reader, labeled with the message type.
+    ///
+    /// Readers seeing the messages a frame late are dashed, unordered ones are drawn in the ambiguity color.
+    /// Messages without writers or readers in this schedule get their own node.
+    pub fn to_dot(&self, settings: &Settings) -> String {
+        let style = &settings.style;
+        let mut dot = DotGraph::new(
+            "",
+            "digraph",
+            &[
+                ("splines", style.edge_style.as_dot()),
+                ("rankdir", style.schedule_rankdir.as_dot()),
+                ("bgcolor", &style.color_background),
+                ("fontname", &style.fontname),
+                ("nodesep", "0.15"),
+            ],
+        )
+        .edge_attributes(&[
+            ("penwidth", &format!("{}", style.penwidth_edge)),
+            ("fontname", &style.fontname),
+        ])
+        .node_attributes(&[("style", "filled"), ("fontname", &style.fontname)]);
+
+        let mut systems: Vec<_> = self.systems.iter().collect();
+        systems.sort_by_key(|(&id, _)| id);
+        for (&id, system) in systems {
+            dot.add_node(
+                &node_index_name(id),
+                &[
+                    ("shape", "box"),
+                    ("label", &system.name),
+                    ("tooltip", &system.full_name),
+                    ("fillcolor", &system.style.bg_color),
+                    ("fontcolor", &system.style.text_color),
+                    ("color", &system.style.border_color),
+                    ("penwidth", &system.style.border_width),
+                ],
+            );
+        }
+
+        for (i, channel) in self.channels.iter().enumerate() {
+            let message = disqualified::ShortName(&channel.message).to_string();
+            let color = &style.color_edge[i % style.color_edge.len()];
+
+            for edge in &channel.edges {
+                let (label, color, line) = match edge.timing {
+                    MessageTiming::SameFrame => (message.clone(), color, "solid"),
+                    MessageTiming::NextFrame => {
+                        (format!("{message}\n(next frame)"), color, "dashed")
+                    }
+                    MessageTiming::Unordered => (
+                        format!("{message}\n(unordered)"),
+                        &style.ambiguity_color,
+                        "dashed",
+                    ),
+                };
+                let tooltip = format!(
+                    "{} → {}: {message}",
+                    self.systems[&edge.writer].name, self.systems[&edge.reader].name
+                );
+                dot.add_edge(
+                    &node_index_name(edge.writer),
+                    &node_index_name(edge.reader),
+                    &[
+                        ("label", &label),
+                        ("tooltip", &tooltip),
+                        ("color", color),
+                        ("fontcolor", color),
+                        ("style", line),
+                    ],
+                );
+            }
+
+            if channel.writers.is_empty() || channel.readers.is_empty() {
+                let message_id = format!("message_{i}");
+                let tooltip = if channel.writers.is_empty() {
+                    format!("{message} is not written in this schedule")
+                } else {
+                    format!("{message} is not read in this schedule")
+                };
+                dot.add_node(
+                    &message_id,
+                    &[
+                        ("shape", "ellipse"),
+                        ("label", &message),
+                        ("tooltip", &tooltip),
+                        ("fillcolor", &style.color_set),
+                        ("fontcolor", &style.color_set_label),
+                        ("color", &style.color_set_border),
+                    ],
+                );
+                for &writer in &channel.writers {
+                    dot.add_edge(&node_index_name(writer), &message_id, &[("color", color)]);
+                }
+                for &reader in &channel.readers {
+                    dot.add_edge(&message_id, &node_index_name(reader), &[("color", color)]);
+                }
+            }
+        }
+
+        dot.finish()
+    }
+}
+
+/// The message type of a `Messages<T>` resource, matching any module path in `bevy_ecs` since it isn't public
+fn message_type(resource: &str) -> Option<&str> {
+    let (path, message) = resource.split_once('<')?;
+    let is_messages = path.starts_with("bevy_ecs::") && path.ends_with("::Messages");
+    is_messages.then_some(message.strip_suffix('>')?)
+}
+
+#[test]
+fn message_type_name() {
+    assert_eq!(
+        message_type("bevy_ecs::message::messages::Messages<my_game::Hit<u8>>"),
+        Some("my_game::Hit<u8>")
+    );
+    assert_eq!(
+        message_type("bevy_time::time::Time<bevy_time::real::Real>"),
+        None
+    );
+    assert_eq!(message_type("my_game::Messages<my_game::Hit>"), None);
+}
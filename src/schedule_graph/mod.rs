@@ -1,141 +1,136 @@
+pub mod ambiguity_baseline;
+pub mod ambiguity_report;
+pub mod analysis;
+#[cfg(feature = "config")]
+pub mod config;
+pub mod data_flow;
+pub mod diff;
+pub mod filter;
+pub mod graph_exchange;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod json;
+pub mod mermaid;
+pub mod message_flow;
+pub mod model;
+pub mod overview;
+pub mod path;
 pub mod settings;
+#[cfg(feature = "svg")]
+pub mod svg;
 pub mod system_style;
+pub mod text;
 
-use bevy_platform::collections::hash_map::HashMap;
 use bevy_platform::collections::hash_set::HashSet;
+pub use model::ScheduleModel;
 pub use settings::Settings;
 
-use std::{any::TypeId, borrow::Cow, collections::VecDeque, fmt::Write, sync::atomic::AtomicUsize};
+use std::{collections::VecDeque, fmt::Write, sync::atomic::AtomicUsize};
 
 use crate::dot::DotGraph;
 use bevy_ecs::{
     schedule::{
         graph::{DiGraph, Direction},
-        ApplyDeferred, NodeId, Schedule, ScheduleGraph, SystemKey, SystemSet,
+        NodeId, Schedule, ScheduleGraph,
     },
-    system::ScheduleSystem,
     world::World,
 };
+use model::{ConditionNode, DependencyEdge, SetKind, SystemAccess, SystemNode};
+use settings::{Focus, FocusDirection};
 
 /// Formats the schedule into a dot graph.
 pub fn schedule_graph_dot(schedule: &Schedule, world: &World, settings: &Settings) -> String {
-    let graph = schedule.graph();
-    let hierarchy = graph.hierarchy().graph();
-
-    let mut dependency = graph.dependency().graph().clone();
-    if settings.remove_transitive_edges {
-        remove_transitive_edges(&mut dependency);
-    }
+    let model = ScheduleModel::new(schedule, world, settings);
+    schedule_model_dot(&model, settings)
+}
 
-    let included_systems_sets = included_systems_sets(graph, settings);
+/// Formats the components and resources the systems of the schedule write and read into a dot graph,
+/// see [`data_flow::schedule_model_data_flow_dot`].
+pub fn schedule_graph_data_flow_dot(
+    schedule: &Schedule,
+    world: &World,
+    settings: &Settings,
+) -> String {
+    let model = ScheduleModel::new(schedule, world, settings);
+    let unfiltered = ScheduleModel::unfiltered(schedule, world, settings);
+    data_flow::schedule_model_data_flow_dot(&model, &unfiltered, settings)
+}
 
-    let mut system_sets: Vec<_> = graph.system_sets.iter().collect();
-    system_sets.sort_by_key(|&(node_id, ..)| node_id);
+/// Formats the schedule as JSON, see [`json::schedule_model_json`] for the schema.
+pub fn schedule_graph_json(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::with_transitive_flags(schedule, world, settings);
+    json::schedule_model_json(&model)
+}
 
-    // collect sets and systems
-    let mut systems_freestanding = Vec::new();
-    let mut systems_in_single_set = HashMap::<NodeId, Vec<_>>::default();
-    let mut systems_in_multiple_sets = HashMap::<Option<NodeId>, Vec<_>>::default();
+/// Formats the schedule as GraphML, see [`graph_exchange::schedule_model_graphml`] for the attributes.
+pub fn schedule_graph_graphml(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::with_transitive_flags(schedule, world, settings);
+    graph_exchange::schedule_model_graphml(&model)
+}
 
-    for (system_id, system, _condition) in graph
-        .systems
-        .iter()
-        .filter(|(id, ..)| included_systems_sets.contains(&NodeId::System(*id)))
-    {
-        let node_id = NodeId::System(system_id);
-        let single_parent = iter_single(hierarchy_parents(node_id, graph));
-
-        match single_parent {
-            IterSingleResult::Empty => systems_freestanding.push((node_id, system)),
-            IterSingleResult::Single(parent) => {
-                systems_in_single_set
-                    .entry(parent)
-                    .or_default()
-                    .push((node_id, system));
-            }
-            IterSingleResult::Multiple(parents) => {
-                let first_common_ancestor = lowest_common_ancestor(&parents, hierarchy);
+/// Formats the schedule as GEXF, with the same attributes as [`schedule_graph_graphml`].
+pub fn schedule_graph_gexf(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::with_transitive_flags(schedule, world, settings);
+    graph_exchange::schedule_model_gexf(&model)
+}
 
-                systems_in_multiple_sets
-                    .entry(first_common_ancestor)
-                    .or_default()
-                    .push((node_id, system))
-            }
-        }
-    }
+/// Formats the schedule as a mermaid flowchart.
+pub fn schedule_graph_mermaid(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::new(schedule, world, settings);
+    mermaid::schedule_model_mermaid(&model, settings)
+}
 
-    let mut sets_freestanding = Vec::new();
-    let mut sets_in_single_set = HashMap::<NodeId, Vec<_>>::default();
-    let mut sets_in_multiple_sets = HashMap::<Option<NodeId>, Vec<_>>::default();
+/// Formats the ambiguities of the schedule as a report grouped by component and by system,
+/// whether or not [`Settings::ambiguity_enable`] is set.
+pub fn schedule_graph_ambiguity_report(
+    schedule: &Schedule,
+    world: &World,
+    settings: &Settings,
+) -> String {
+    let model = ScheduleModel::with_ambiguities(schedule, world, settings);
+    ambiguity_report::AmbiguityReport::new(&model).to_text()
+}
 
-    let mut collapsed_sets = HashSet::default();
-    let mut collapsed_set_children = HashMap::default();
+/// Formats the schedule as indented plain text.
+pub fn schedule_graph_text(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::new(schedule, world, settings);
+    text::schedule_model_text(&model, settings)
+}
 
-    for &(set_id, set, _condition) in system_sets
-        .iter()
-        .filter(|&&(id, ..)| graph.system_sets.get(id).unwrap().system_type().is_none())
-        .filter(|(id, ..)| included_systems_sets.contains(&NodeId::Set(*id)))
-    {
-        let node_id = NodeId::Set(set_id);
-        let single_parent = iter_single(hierarchy_parents(node_id, graph));
-
-        match single_parent {
-            IterSingleResult::Empty => sets_freestanding.push((node_id, set)),
-            IterSingleResult::Single(parent) => {
-                sets_in_single_set
-                    .entry(parent)
-                    .or_default()
-                    .push((node_id, set));
-            }
-            IterSingleResult::Multiple(parents) => {
-                let first_common_ancestor = lowest_common_ancestor(&parents, hierarchy);
+/// Renders the schedule as a self-contained interactive HTML page.
+#[cfg(feature = "html")]
+pub fn schedule_graph_html(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::new(schedule, world, settings);
+    html::schedule_model_html(&model, settings)
+}
 
-                sets_in_multiple_sets
-                    .entry(first_common_ancestor)
-                    .or_default()
-                    .push((node_id, set));
-            }
-        }
-    }
+/// Lays out and renders the schedule as SVG, without requiring graphviz.
+#[cfg(feature = "svg")]
+pub fn schedule_graph_svg(schedule: &Schedule, world: &World, settings: &Settings) -> String {
+    let model = ScheduleModel::new(schedule, world, settings);
+    svg::schedule_model_svg(&model, settings)
+}
 
-    for &(set_id, ..) in system_sets
-        .iter()
-        .filter(|&&(id, ..)| graph.system_sets.get(id).unwrap().system_type().is_none())
-        .filter(|(id, ..)| included_systems_sets.contains(&NodeId::Set(*id)))
-    {
-        let node_id = NodeId::Set(set_id);
-        if settings.collapse_single_system_sets {
-            let children = systems_in_single_set
-                .get(&node_id)
-                .map_or(&[] as &[_], |vec| vec.as_slice());
-            let children_in_multiple = systems_in_multiple_sets
-                .get(&Some(node_id))
-                .map_or(&[] as &[_], |vec| vec.as_slice());
-
-            let children_sets_empty = sets_in_single_set
-                .get(&node_id)
-                .is_none_or(|vec| vec.is_empty());
-            let children_sets_in_multiple_empty = systems_in_multiple_sets
-                .get(&Some(node_id))
-                .is_none_or(|vec| vec.is_empty());
-
-            if children_in_multiple.is_empty()
-                && children.len() <= 1
-                && children_sets_empty
-                && children_sets_in_multiple_empty
-            {
-                collapsed_sets.insert(node_id);
+/// Formats an already built [`ScheduleModel`] into a dot graph.
+pub fn schedule_model_dot(model: &ScheduleModel, settings: &Settings) -> String {
+    schedule_model_dot_highlighted(model, settings, &Highlight::default())
+}
 
-                for &(child, ..) in children {
-                    collapsed_set_children.insert(child, node_id);
-                }
-                for &(child, ..) in children_in_multiple {
-                    collapsed_set_children.insert(child, node_id);
-                }
-            }
-        }
-    }
+/// Systems, sets and dependencies to draw in a highlight color, e.g. the paths of [`path::DependencyPaths`]
+#[derive(Default, Clone)]
+pub struct Highlight {
+    pub nodes: HashSet<NodeId>,
+    /// Highlighted dependency edges, also drawn when they are transitive
+    pub dependencies: HashSet<(NodeId, NodeId)>,
+    pub color: String,
+}
 
+/// Formats an already built [`ScheduleModel`] into a dot graph, drawing the highlighted nodes and edges in the highlight color.
+pub fn schedule_model_dot_highlighted(
+    model: &ScheduleModel,
+    settings: &Settings,
+    highlight: &Highlight,
+) -> String {
     let mut dot = DotGraph::new(
         "",
         "digraph",
@@ -153,19 +148,8 @@ pub fn schedule_graph_dot(schedule: &Schedule, world: &World, settings: &Setting
 
     let context = ScheduleGraphContext {
         settings,
-        world,
-        graph: schedule.graph(),
-        dependency: &mut dependency,
-        included_systems_sets,
-        systems_freestanding,
-        systems_in_single_set,
-        systems_in_multiple_sets,
-        sets_freestanding,
-        sets_in_single_set,
-        sets_in_multiple_sets,
-        collapsed_sets,
-        collapsed_set_children,
-
+        model,
+        highlight,
         color_edge_idx: AtomicUsize::new(0),
     };
 
@@ -182,24 +166,8 @@ pub fn schedule_graph_dot(schedule: &Schedule, world: &World, settings: &Setting
 
 struct ScheduleGraphContext<'a> {
     settings: &'a Settings,
-    world: &'a World,
-
-    graph: &'a ScheduleGraph,
-    dependency: &'a DiGraph<NodeId>,
-
-    included_systems_sets: HashSet<NodeId>,
-
-    systems_freestanding: Vec<(NodeId, &'a ScheduleSystem)>,
-    systems_in_single_set: HashMap<NodeId, Vec<(NodeId, &'a ScheduleSystem)>>,
-    systems_in_multiple_sets: HashMap<Option<NodeId>, Vec<(NodeId, &'a ScheduleSystem)>>,
-
-    sets_freestanding: Vec<(NodeId, &'a dyn SystemSet)>,
-    sets_in_single_set: HashMap<NodeId, Vec<(NodeId, &'a dyn SystemSet)>>,
-    sets_in_multiple_sets: HashMap<Option<NodeId>, Vec<(NodeId, &'a dyn SystemSet)>>,
-
-    collapsed_sets: HashSet<NodeId>,
-    // map from child to collapsed set
-    collapsed_set_children: HashMap<NodeId, NodeId>,
+    model: &'a ScheduleModel,
+    highlight: &'a Highlight,
 
     color_edge_idx: AtomicUsize,
 }
@@ -207,109 +175,76 @@ struct ScheduleGraphContext<'a> {
 impl ScheduleGraphContext<'_> {
     /// Add sets with systems recursively, as well as sets belonging to multiple sets without a common ancestor
     fn add_sets(&self, dot: &mut DotGraph) {
-        for &(set_id, set) in self.sets_freestanding.iter() {
-            assert!(self.included_systems_sets.contains(&set_id));
-            self.add_set(set_id, set, dot);
+        for &set_id in self.model.sets_freestanding.iter() {
+            self.add_set(set_id, dot);
         }
 
-        for &(set_id, set) in self
-            .sets_in_multiple_sets
-            .get(&None)
-            .map(|vec| vec.as_slice())
-            .unwrap_or_default()
-        {
-            self.add_set_in_multiple_sets(dot, set_id, set);
+        for &set_id in self.model.sets_in_multiple_sets(None) {
+            self.add_set_in_multiple_sets(dot, set_id);
         }
     }
 
     /// Add freestanding systems that do not belong to a set, as well as systems in multiple sets without a common ancestor
     fn add_freestanding_systems(&self, dot: &mut DotGraph) {
-        for &(system_id, system) in self.systems_freestanding.iter() {
-            assert!(self.included_systems_sets.contains(&system_id));
-            let name = self.system_name(system);
+        for &system_id in self.model.systems_freestanding.iter() {
+            let system = self.model.system(system_id).unwrap();
             dot.add_node(
                 &node_index_name(system_id),
-                &[("label", &name), ("tooltip", &system.name())],
+                &self.highlighted(
+                    system_id,
+                    vec![
+                        ("label", &self.system_label(system, &system.name)),
+                        ("tooltip", &system.full_name),
+                    ],
+                ),
             );
+            self.add_conditions(dot, system_id, &system.conditions);
         }
 
-        for &(system_id, system) in self
-            .systems_in_multiple_sets
-            .get(&None)
-            .map(|vec| vec.as_slice())
-            .unwrap_or_default()
-        {
-            self.add_system_in_multiple_sets(dot, system_id, system);
+        for &system_id in self.model.systems_in_multiple_sets(None) {
+            self.add_system_in_multiple_sets(dot, system_id);
         }
     }
 
     /// Add dependency edges between nodes
     fn add_dependencies(&self, dot: &mut DotGraph) {
-        for (from, to) in self.dependency.all_edges() {
-            if !self.included_systems_sets.contains(&from)
-                || !self.included_systems_sets.contains(&to)
-            {
+        let highlight_penwidth = format!("{}", self.settings.style.penwidth_edge * 2.0);
+        for &DependencyEdge {
+            from,
+            to,
+            transitive,
+        } in &self.model.dependencies
+        {
+            let highlighted = self.highlight.dependencies.contains(&(from, to));
+            if transitive && self.settings.remove_transitive_edges && !highlighted {
                 continue;
             }
 
             let color_edge = self.next_edge_color();
-            dot.add_edge(
-                &self.node_ref(from),
-                &self.node_ref(to),
-                &[
-                    ("lhead", &self.lref(to)),
-                    ("ltail", &self.lref(from)),
-                    ("tooltip", &self.edge_tooltip(from, to)),
-                    ("color", color_edge),
-                ],
-            );
+            let mut attrs = vec![
+                ("lhead", self.lref(to)),
+                ("ltail", self.lref(from)),
+                ("tooltip", self.edge_tooltip(from, to)),
+            ];
+            if highlighted {
+                attrs.push(("color", self.highlight.color.clone()));
+                attrs.push(("penwidth", highlight_penwidth.clone()));
+            } else {
+                attrs.push(("color", color_edge.to_owned()));
+            }
+            let attrs: Vec<_> = attrs.iter().map(|(k, v)| (*k, v.as_str())).collect();
+            dot.add_edge(&self.node_ref(from), &self.node_ref(to), &attrs);
         }
     }
 
     /// Add ambiguity edges
     fn add_ambiguities(&self, dot: &mut DotGraph) {
-        let mut conflicting_systems = self.graph.conflicting_systems().to_vec();
-        conflicting_systems.sort();
-
-        for (system_a, system_b, conflicts) in conflicting_systems {
-            if !self
-                .included_systems_sets
-                .contains(&NodeId::System(system_a))
-                || !self
-                    .included_systems_sets
-                    .contains(&NodeId::System(system_b))
-            {
-                continue;
-            }
-
-            if conflicts.is_empty() && !self.settings.ambiguity_enable_on_world {
-                continue;
-            }
-
-            if let Some(include_ambiguity) = &self.settings.include_ambiguity {
-                let system_a = self.graph.systems.get(system_a).unwrap();
-                let system_b = self.graph.systems.get(system_b).unwrap();
-                if !include_ambiguity(
-                    &system_a.system(),
-                    &system_b.system(),
-                    &conflicts,
-                    self.world,
-                ) {
-                    continue;
-                }
-            }
-
-            let label = if conflicts.is_empty() {
+        for ambiguity in &self.model.ambiguities {
+            let label = if ambiguity.conflicts.is_empty() {
                 "World".to_owned()
             } else {
-                let component_names = conflicts.iter().map(|&component_id| {
-                    let component_name = self
-                        .world
-                        .components()
-                        .get_info(component_id)
-                        .unwrap()
-                        .name();
-                    let pretty_name = disqualified::ShortName(component_name.as_ref());
+                let component_names = ambiguity.conflicts.iter().map(|component_name| {
+                    let pretty_name = disqualified::ShortName(component_name);
 
                     format!(
                         r#"<tr><td bgcolor="{}">{}</td></tr>"#,
@@ -322,8 +257,8 @@ impl ScheduleGraphContext<'_> {
             };
 
             dot.add_edge(
-                &self.system_node_ref(system_a),
-                &self.system_node_ref(system_b),
+                &self.node_ref(ambiguity.system_a),
+                &self.node_ref(ambiguity.system_b),
                 &[
                     ("dir", "none"),
                     ("constraint", "false"),
@@ -332,10 +267,7 @@ impl ScheduleGraphContext<'_> {
                     ("label", &label),
                     (
                         "labeltooltip",
-                        &self.edge_tooltip_undirected(
-                            NodeId::System(system_a),
-                            NodeId::System(system_b),
-                        ),
+                        &self.edge_tooltip_undirected(ambiguity.system_a, ambiguity.system_b),
                     ),
                 ],
             );
@@ -344,12 +276,11 @@ impl ScheduleGraphContext<'_> {
 }
 
 impl ScheduleGraphContext<'_> {
-    fn add_set_in_multiple_sets(&self, dot: &mut DotGraph, set_id: NodeId, set: &dyn SystemSet) {
-        assert!(self.included_systems_sets.contains(&set_id));
-        self.add_set(set_id, set, dot);
+    fn add_set_in_multiple_sets(&self, dot: &mut DotGraph, set_id: NodeId) {
+        self.add_set(set_id, dot);
 
-        for parent in hierarchy_parents(set_id, self.graph) {
-            assert!(self.included_systems_sets.contains(&parent));
+        for &parent in &self.model.set(set_id).unwrap().parents {
+            assert!(self.model.contains(parent));
             dot.add_edge(
                 &self.node_ref(parent),
                 &self.node_ref(set_id),
@@ -364,107 +295,99 @@ impl ScheduleGraphContext<'_> {
     }
 
     // add regular set and system hierarchy
-    fn add_set(&self, set_id: NodeId, set: &dyn SystemSet, dot: &mut DotGraph) {
-        let name = format!("{set:?}");
+    fn add_set(&self, set_id: NodeId, dot: &mut DotGraph) {
+        let set = self.model.set(set_id).unwrap();
+        let name = &set.name;
+        self.add_conditions(dot, set_id, &set.conditions);
 
-        if self.collapsed_sets.contains(&set_id) {
+        if self.model.collapsed_sets.contains(&set_id) {
             dot.add_node(
                 &node_index_name(set_id),
-                &[("label", &name), ("tooltip", &name)],
+                &self.highlighted(set_id, vec![("label", name), ("tooltip", name)]),
             );
+            for &system_id in self.model.systems_in_single_set(set_id) {
+                let system = self.model.system(system_id).unwrap();
+                self.add_conditions(dot, system_id, &system.conditions);
+            }
 
             return;
         }
 
+        let (border_style, border_color) =
+            if self.settings.show_conditions && !set.conditions.is_empty() {
+                (
+                    "rounded,filled,dashed",
+                    &self.settings.style.color_condition,
+                )
+            } else {
+                ("rounded,filled", &self.settings.style.color_set_border)
+            };
+
         let system_set_cluster_name = node_index_name(set_id); // in sync with system_cluster_name
         let mut system_set_graph = DotGraph::subgraph(
             &system_set_cluster_name,
-            &[
-                ("style", "rounded,filled"),
-                ("label", &name),
-                ("tooltip", &name),
-                ("fillcolor", &self.settings.style.color_set),
-                ("fontcolor", &self.settings.style.color_set_label),
-                ("color", &self.settings.style.color_set_border),
-                ("penwidth", "2"),
-            ],
+            &self.highlighted(
+                set_id,
+                vec![
+                    ("style", border_style),
+                    ("label", name),
+                    ("tooltip", name),
+                    ("fillcolor", &self.settings.style.color_set),
+                    ("fontcolor", &self.settings.style.color_set_label),
+                    ("color", border_color),
+                    ("penwidth", "2"),
+                ],
+            ),
         );
 
         system_set_graph.add_invisible_node(&marker_name(set_id));
 
-        for &(nested_set_id, nested_set) in self
-            .sets_in_single_set
-            .get(&set_id)
-            .map(|sets| sets.as_slice())
-            .unwrap_or(&[])
-        {
-            self.add_set(nested_set_id, nested_set, &mut system_set_graph);
+        for &nested_set_id in self.model.sets_in_single_set(set_id) {
+            self.add_set(nested_set_id, &mut system_set_graph);
         }
 
-        for &(nested_set_id, nested_set) in self
-            .sets_in_multiple_sets
-            .get(&Some(set_id))
-            .map(|vec| vec.as_slice())
-            .unwrap_or_default()
-        {
-            self.add_set_in_multiple_sets(&mut system_set_graph, nested_set_id, nested_set);
+        for &nested_set_id in self.model.sets_in_multiple_sets(Some(set_id)) {
+            self.add_set_in_multiple_sets(&mut system_set_graph, nested_set_id);
         }
 
-        let systems = self
-            .systems_in_single_set
-            .get(&set_id)
-            .map(|systems| systems.as_slice())
-            .unwrap_or(&[]);
-
-        for &(system_id, system) in systems.iter() {
-            let name = self.system_name(system);
-            let node_style = self.settings.get_system_style(system);
+        for &system_id in self.model.systems_in_single_set(set_id) {
+            let system = self.model.system(system_id).unwrap();
+            let node_style = &system.style;
+            let border_width = node_style.border_width.to_string();
 
             system_set_graph.add_node(
                 &self.node_ref(system_id),
-                &[
-                    ("label", &name),
-                    ("tooltip", &system.name()),
-                    ("fillcolor", &node_style.bg_color),
-                    ("fontname", &self.settings.style.fontname),
-                    ("fontcolor", &node_style.text_color),
-                    ("color", &node_style.border_color),
-                    ("penwidth", &node_style.border_width.to_string()),
-                ],
+                &self.highlighted(
+                    system_id,
+                    vec![
+                        ("label", &self.system_label(system, &system.name)),
+                        ("tooltip", &system.full_name),
+                        ("fillcolor", &node_style.bg_color),
+                        ("fontname", &self.settings.style.fontname),
+                        ("fontcolor", &node_style.text_color),
+                        ("color", &node_style.border_color),
+                        ("penwidth", &border_width),
+                    ],
+                ),
             );
+            self.add_conditions(&mut system_set_graph, system_id, &system.conditions);
         }
 
-        for &(system_id, system) in self
-            .systems_in_multiple_sets
-            .get(&Some(set_id))
-            .map(|vec| vec.as_slice())
-            .unwrap_or_default()
-        {
-            self.add_system_in_multiple_sets(&mut system_set_graph, system_id, system);
+        for &system_id in self.model.systems_in_multiple_sets(Some(set_id)) {
+            self.add_system_in_multiple_sets(&mut system_set_graph, system_id);
         }
 
         dot.add_sub_graph(system_set_graph);
     }
 
-    fn add_system_in_multiple_sets(
-        &self,
-        dot: &mut DotGraph,
-        system_id: NodeId,
-        system: &ScheduleSystem,
-    ) {
-        assert!(self.included_systems_sets.contains(&system_id));
-        let mut name = self.system_name(system);
-        let name = name.to_mut();
+    fn add_system_in_multiple_sets(&self, dot: &mut DotGraph, system_id: NodeId) {
+        let system = self.model.system(system_id).unwrap();
+        let mut name = system.name.clone();
         name.push_str("\nIn multiple sets");
 
-        for parent in hierarchy_parents(system_id, self.graph) {
-            assert!(self.included_systems_sets.contains(&parent));
-            let parent_set = self
-                .graph
-                .system_sets
-                .get(parent.as_set().unwrap())
-                .unwrap();
-            let _ = write!(name, ", {parent_set:?}");
+        for &parent in &system.parents {
+            assert!(self.model.contains(parent));
+            let _ = write!(name, ", {}", self.model.node_name(parent));
 
             dot.add_edge(
                 &self.node_ref(system_id),
@@ -478,16 +401,70 @@ impl ScheduleGraphContext<'_> {
         }
         dot.add_node(
             &self.node_ref(system_id),
-            &[("label", name), ("tooltip", &system.name())],
+            &self.highlighted(
+                system_id,
+                vec![
+                    ("label", &self.system_label(system, &name)),
+                    ("tooltip", &system.full_name),
+                ],
+            ),
         );
+        self.add_conditions(dot, system_id, &system.conditions);
+    }
+
+    /// The label of a system node, with a row for every component and resource
+    /// the system accesses if [`Settings::show_access`] is set
+    fn system_label(&self, system: &SystemNode, label: &str) -> String {
+        if self.settings.show_access {
+            access_label(label, &system.access)
+        } else {
+            label.to_owned()
+        }
+    }
+
+    /// Add a node for every run condition of a system or set, with an edge to it
+    fn add_conditions(&self, dot: &mut DotGraph, node_id: NodeId, conditions: &[ConditionNode]) {
+        if !self.settings.show_conditions {
+            return;
+        }
+        let color = &self.settings.style.color_condition;
+        for (i, condition) in conditions.iter().enumerate() {
+            let condition_id = format!("condition_{i}_{node_id:?}");
+            let name = disqualified::ShortName(&condition.name).to_string();
+            dot.add_node(
+                &condition_id,
+                &[
+                    ("shape", "diamond"),
+                    ("label", &access_label(&name, &condition.access)),
+                    ("tooltip", &condition.name),
+                    ("fillcolor", &self.settings.style.color_set),
+                    ("fontcolor", &self.settings.style.color_set_label),
+                    ("color", color),
+                ],
+            );
+            dot.add_edge(
+                &condition_id,
+                &self.node_ref(node_id),
+                &[
+                    ("lhead", &self.lref(node_id)),
+                    ("style", "dashed"),
+                    ("arrowhead", "odot"),
+                    ("color", color),
+                    (
+                        "tooltip",
+                        &format!("{} runs if {name}", self.model.node_name(node_id)),
+                    ),
+                ],
+            );
+        }
     }
 
     fn edge_tooltip(&self, a: NodeId, b: NodeId) -> String {
-        format!("{} → {}", self.full_name(a), self.full_name(b))
+        format!("{} → {}", self.model.node_name(a), self.model.node_name(b))
     }
 
     fn edge_tooltip_undirected(&self, a: NodeId, b: NodeId) -> String {
-        format!("{} — {}", self.full_name(a), self.full_name(b))
+        format!("{} — {}", self.model.node_name(a), self.model.node_name(b))
     }
 
     fn next_edge_color(&self) -> &str {
@@ -502,15 +479,19 @@ impl ScheduleGraphContext<'_> {
     }
 }
 
+fn all_systems_sets(graph: &ScheduleGraph) -> HashSet<NodeId> {
+    graph
+        .systems
+        .iter()
+        .map(|(id, ..)| NodeId::System(id))
+        .chain(graph.system_sets.iter().map(|(id, ..)| NodeId::Set(id)))
+        .collect()
+}
+
 fn included_systems_sets(graph: &ScheduleGraph, settings: &Settings) -> HashSet<NodeId> {
-    let Some(include_system) = &settings.include_system else {
-        return graph
-            .systems
-            .iter()
-            .map(|(id, ..)| NodeId::System(id))
-            .chain(graph.system_sets.iter().map(|(id, ..)| NodeId::Set(id)))
-            .collect();
-    };
+    if settings.include_system.is_none() && settings.filter.is_none() && settings.focus.is_none() {
+        return all_systems_sets(graph);
+    }
 
     let hierarchy = graph.hierarchy().graph();
 
@@ -525,10 +506,34 @@ fn included_systems_sets(graph: &ScheduleGraph, settings: &Settings) -> HashSet<
                 .is_none()
     });
 
+    let focused = settings
+        .focus
+        .as_ref()
+        .map(|focus| focused_systems(graph, focus, settings));
+
     let systems_of_interest: HashSet<NodeId> = graph
         .systems
         .iter()
-        .filter(|&(_, system, _)| include_system(system))
+        .filter(|&(id, ..)| {
+            focused
+                .as_ref()
+                .is_none_or(|focused| focused.contains(&NodeId::System(id)))
+        })
+        .filter(|&(id, system, _)| {
+            let included = settings
+                .include_system
+                .as_ref()
+                .is_none_or(|include_system| include_system(system));
+            included
+                && settings.filter.as_ref().is_none_or(|filter| {
+                    let set_names = if filter.uses_sets() {
+                        ancestor_set_names(graph, NodeId::System(id), settings)
+                    } else {
+                        Vec::new()
+                    };
+                    filter.matches(&system.name().to_string(), &set_names)
+                })
+        })
         .map(|(id, ..)| NodeId::System(id))
         .collect();
 
@@ -569,92 +574,143 @@ fn included_systems_sets(graph: &ScheduleGraph, settings: &Settings) -> HashSet<
         }
     }
 
-    for (from, to) in graph.dependency().graph().all_edges() {
-        if systems_of_interest.contains(&from) {
-            included_systems_sets.insert(to);
-            include_ancestors(to, hierarchy, &mut included_systems_sets);
-        }
+    // with a focus the neighbourhood is already limited to the requested depth
+    if settings.focus.is_none() {
+        for (from, to) in graph.dependency().graph().all_edges() {
+            if systems_of_interest.contains(&from) {
+                included_systems_sets.insert(to);
+                include_ancestors(to, hierarchy, &mut included_systems_sets);
+            }
 
-        if systems_of_interest.contains(&to) {
-            included_systems_sets.insert(from);
-            include_ancestors(to, hierarchy, &mut included_systems_sets);
+            if systems_of_interest.contains(&to) {
+                included_systems_sets.insert(from);
+                include_ancestors(to, hierarchy, &mut included_systems_sets);
+            }
         }
     }
 
     included_systems_sets
 }
 
-impl ScheduleGraphContext<'_> {
-    fn system_name(&self, system: &ScheduleSystem) -> Cow<'_, str> {
-        (*self.settings.system_name)(system).into()
-    }
+/// Systems within `focus.depth` dependency hops of the focused systems, including them
+fn focused_systems(graph: &ScheduleGraph, focus: &Focus, settings: &Settings) -> HashSet<NodeId> {
+    let start: Vec<NodeId> = graph
+        .systems
+        .iter()
+        .filter(|&(id, system, _)| {
+            let set_names = if focus.filter.uses_sets() {
+                ancestor_set_names(graph, NodeId::System(id), settings)
+            } else {
+                Vec::new()
+            };
+            focus.filter.matches(&system.name().to_string(), &set_names)
+        })
+        .map(|(id, ..)| NodeId::System(id))
+        .collect();
 
-    fn system_set_name(&self, system_set: &dyn SystemSet) -> Cow<'_, str> {
-        (*self.settings.system_set_name)(system_set).into()
-    }
+    let directions: &[Direction] = match focus.direction {
+        FocusDirection::Upstream => &[Direction::Incoming],
+        FocusDirection::Downstream => &[Direction::Outgoing],
+        FocusDirection::Both => &[Direction::Incoming, Direction::Outgoing],
+    };
 
-    fn full_name(&self, node_id: NodeId) -> Cow<'_, str> {
-        match node_id {
-            NodeId::System(key) => self.system_name(&self.graph.systems.get(key).unwrap().system()),
-            NodeId::Set(key) => self.system_set_name(self.graph.system_sets.get(key).unwrap()),
+    let hierarchy = graph.hierarchy().graph();
+    let dependency = graph.dependency().graph();
+
+    let mut focused: HashSet<NodeId> = start.iter().copied().collect();
+    for &direction in directions {
+        let mut visited: HashSet<NodeId> = start.iter().copied().collect();
+        let mut frontier = start.clone();
+        for _ in 0..focus.depth {
+            let mut next = Vec::new();
+            for &system in &frontier {
+                // a system is ordered by the orderings of all sets it is in,
+                // and an ordering on a set applies to all systems in it
+                let neighbors = ancestors_of_node(system, hierarchy)
+                    .flat_map(|node| dependency.neighbors_directed(node, direction));
+                for neighbor in neighbors {
+                    for neighbor_system in systems_in(neighbor, hierarchy) {
+                        if visited.insert(neighbor_system) {
+                            next.push(neighbor_system);
+                        }
+                    }
+                }
+            }
+            frontier = next;
         }
+        focused.extend(visited);
     }
+    focused
+}
 
+/// The node itself if it is a system, or all systems in the set and its nested sets
+fn systems_in(node: NodeId, hierarchy: &DiGraph<NodeId>) -> Vec<NodeId> {
+    if node.is_system() {
+        return vec![node];
+    }
+    hierarchy
+        .neighbors_directed(node, Direction::Outgoing)
+        .flat_map(|child| systems_in(child, hierarchy))
+        .collect()
+}
+
+impl ScheduleGraphContext<'_> {
     fn is_non_system_set(&self, node_id: NodeId) -> bool {
-        let NodeId::Set(key) = node_id else {
-            return false;
-        };
-        self.graph
-            .system_sets
-            .get(key)
-            .unwrap()
-            .system_type()
-            .is_none()
+        self.model
+            .set(node_id)
+            .is_some_and(|set| set.kind == SetKind::Regular)
+    }
+
+    /// Sets the border color and width of highlighted nodes and clusters
+    fn highlighted<'b>(
+        &'b self,
+        node_id: NodeId,
+        mut attrs: Vec<(&'b str, &'b str)>,
+    ) -> Vec<(&'b str, &'b str)> {
+        if !self.highlight.nodes.contains(&node_id) {
+            return attrs;
+        }
+        for (key, value) in [("color", self.highlight.color.as_str()), ("penwidth", "3")] {
+            match attrs.iter_mut().find(|(k, _)| *k == key) {
+                Some(attr) => attr.1 = value,
+                None => attrs.push((key, value)),
+            }
+        }
+        attrs
     }
 
     // lhead/ltail
     fn lref(&self, node_id: NodeId) -> String {
-        if self.is_non_system_set(node_id) && !self.collapsed_sets.contains(&node_id) {
+        if self.is_non_system_set(node_id) && !self.model.collapsed_sets.contains(&node_id) {
             set_cluster_name(node_id)
         } else {
             String::new()
         }
     }
 
-    // PERF: O(n)
-    fn system_of_system_type(&self, set: &dyn SystemSet) -> Option<SystemKey> {
-        self.graph.systems.iter().find_map(|(id, system, _)| {
-            if system.name().starts_with("print_schedule_graph") {
-                dbg!(&system, system.default_system_sets(), set);
-            }
-            let is_system_set = system.default_system_sets().iter().any(|s| s.0 == set);
-            is_system_set.then_some(id)
-        })
-    }
-
-    fn system_node_ref(&self, node_id: SystemKey) -> String {
-        if let Some(collapsed_set) = self.collapsed_set_children.get(&NodeId::System(node_id)) {
-            node_index_name(*collapsed_set)
-        } else {
-            node_index_name(NodeId::System(node_id))
-        }
-    }
-
     fn node_ref(&self, node_id: NodeId) -> String {
         match node_id {
-            NodeId::System(system) => self.system_node_ref(system),
-            NodeId::Set(_) if self.collapsed_sets.contains(&node_id) => node_index_name(node_id),
-            NodeId::Set(set) => {
-                let set = self.graph.system_sets.get(set).unwrap();
-
-                if set.system_type() == Some(TypeId::of::<ApplyDeferred>()) {
-                    "ApplyDeferred".to_owned()
-                } else if set.system_type().is_some() {
-                    let system_node = self.system_of_system_type(set);
-                    if let Some(system_node) = system_node {
-                        self.system_node_ref(system_node)
-                    } else {
-                        let name = format!("{:?}", disqualified::ShortName(&format!("{set:?}")));
+            NodeId::System(_) => {
+                if let Some(collapsed_set) = self.model.collapsed_set_children.get(&node_id) {
+                    node_index_name(*collapsed_set)
+                } else {
+                    node_index_name(node_id)
+                }
+            }
+            NodeId::Set(_) if self.model.collapsed_sets.contains(&node_id) => {
+                node_index_name(node_id)
+            }
+            NodeId::Set(_) => {
+                let set = self.model.set(node_id).unwrap();
+
+                match set.kind {
+                    SetKind::Regular => marker_name(node_id),
+                    SetKind::ApplyDeferred => "ApplyDeferred".to_owned(),
+                    SetKind::SystemType {
+                        system: Some(system),
+                    } => self.node_ref(system),
+                    SetKind::SystemType { system: None } => {
+                        let name = format!("{:?}", disqualified::ShortName(&set.name));
                         if name.starts_with("SystemTypeSet(fn FunctionSystem") {
                             let fn_name = name
                                 .trim_end_matches(">())")
@@ -665,14 +721,9 @@ impl ScheduleGraphContext<'_> {
 
                             format!("<missing> {fn_name}")
                         } else {
-                            format!(
-                                "<missing> {:?}",
-                                disqualified::ShortName(&format!("{set:?}"))
-                            )
+                            format!("<missing> {name}")
                         }
                     }
-                } else {
-                    marker_name(node_id)
                 }
             }
         }
@@ -687,6 +738,64 @@ fn set_cluster_name(id: NodeId) -> String {
 fn node_index_name(node_id: NodeId) -> String {
     format!("node_{node_id:?}")
 }
+
+/// A label with a row for every component and resource in `access`, or just `label` if there are none
+fn access_label(label: &str, access: &SystemAccess) -> String {
+    if access.is_empty() {
+        return label.to_owned();
+    }
+
+    let short_name = |name: &String| disqualified::ShortName(name).to_string();
+    let mut rows = Vec::new();
+    if access.writes_all {
+        rows.push("&mut World".to_owned());
+    } else if access.reads_all {
+        rows.push("&World".to_owned());
+    }
+    rows.extend(
+        access
+            .resources_read
+            .iter()
+            .map(|name| format!("Res<{}>", short_name(name))),
+    );
+    rows.extend(
+        access
+            .resources_written
+            .iter()
+            .map(|name| format!("ResMut<{}>", short_name(name))),
+    );
+    rows.extend(
+        access
+            .components_read
+            .iter()
+            .map(|name| format!("&{}", short_name(name))),
+    );
+    rows.extend(
+        access
+            .components_written
+            .iter()
+            .map(|name| format!("&mut {}", short_name(name))),
+    );
+
+    let title = label
+        .lines()
+        .map(crate::dot::html_escape)
+        .collect::<Vec<_>>()
+        .join("<br/>");
+    let trs = rows
+        .iter()
+        .map(|row| {
+            format!(
+                r#"<tr><td align="left">{}</td></tr>"#,
+                crate::dot::html_escape(row)
+            )
+        })
+        .collect::<String>();
+    format!(
+        r#"RAW:<<table border="0" cellborder="0" cellspacing="0"><tr><td><b>{title}</b></td></tr><hr/>{trs}</table>>"#
+    )
+}
+
 fn marker_name(node_id: NodeId) -> String {
     assert!(node_id.is_set());
     format!("set_marker_node_{node_id:?}")
@@ -771,6 +880,18 @@ impl Iterator for Ancestors<'_> {
     }
 }
 
+/// Names of the sets the node is in, directly or through nested sets
+fn ancestor_set_names(graph: &ScheduleGraph, node: NodeId, settings: &Settings) -> Vec<String> {
+    ancestors_of_node(node, graph.hierarchy().graph())
+        .filter_map(|ancestor| {
+            let set = &graph.system_sets[ancestor.as_set()?];
+            set.system_type()
+                .is_none()
+                .then(|| (settings.system_set_name)(set))
+        })
+        .collect()
+}
+
 fn collect_reachable(
     reachable: &mut HashSet<NodeId>,
     graph: &DiGraph<NodeId>,
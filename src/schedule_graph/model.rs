@@ -0,0 +1,652 @@
+use std::any::TypeId;
+
+use bevy_ecs::{
+    component::ComponentId,
+    query::{ComponentAccessKind, FilteredAccessSet},
+    schedule::{
+        ApplyDeferred, ConditionWithAccess, NodeId, Schedule, ScheduleGraph, SystemKey, SystemSet,
+    },
+    system::System,
+    world::World,
+};
+use bevy_platform::collections::{hash_map::HashMap, hash_set::HashSet};
+
+use super::{
+    all_systems_sets, hierarchy_parents, included_systems_sets, iter_single,
+    lowest_common_ancestor, remove_transitive_edges,
+    settings::{NodeStyle, Settings},
+    IterSingleResult,
+};
+
+/// Format-independent description of a schedule, built once from a [`ScheduleGraph`].
+///
+/// The model only contains the systems and sets matched by the [`Settings`] filters, with names and
+/// styles already resolved. It is what the dot output is rendered from, and can be consumed by other
+/// renderers or tooling without going through the dot text.
+pub struct ScheduleModel {
+    /// `Debug` representation of the schedule label
+    pub label: String,
+
+    /// Included systems, in the order of the schedule graph
+    pub systems: Vec<SystemNode>,
+    /// Included system sets, including the implicit sets of system types
+    pub sets: Vec<SetNode>,
+    /// Dependency edges between included systems and sets, including transitive ones
+    pub dependencies: Vec<DependencyEdge>,
+    /// Conflicting system pairs. Only collected when [`Settings::ambiguity_enable`] is set, or by [`ScheduleModel::with_ambiguities`].
+    pub ambiguities: Vec<Ambiguity>,
+
+    /// Systems without a parent set
+    pub systems_freestanding: Vec<NodeId>,
+    /// Systems with exactly one parent set, keyed by that set
+    pub systems_in_single_set: HashMap<NodeId, Vec<NodeId>>,
+    /// Systems with multiple parent sets, keyed by the lowest common ancestor of those sets
+    pub systems_in_multiple_sets: HashMap<Option<NodeId>, Vec<NodeId>>,
+
+    /// Sets without a parent set
+    pub sets_freestanding: Vec<NodeId>,
+    /// Sets with exactly one parent set, keyed by that set
+    pub sets_in_single_set: HashMap<NodeId, Vec<NodeId>>,
+    /// Sets with multiple parent sets, keyed by the lowest common ancestor of those sets
+    pub sets_in_multiple_sets: HashMap<Option<NodeId>, Vec<NodeId>>,
+
+    /// Sets that are drawn as a single node, see [`Settings::collapse_single_system_sets`]
+    pub collapsed_sets: HashSet<NodeId>,
+    /// Map from a child of a collapsed set to the collapsed set
+    pub collapsed_set_children: HashMap<NodeId, NodeId>,
+
+    remove_transitive_edges: bool,
+    system_indices: HashMap<NodeId, usize>,
+    set_indices: HashMap<NodeId, usize>,
+}
+
+pub struct SystemNode {
+    pub id: NodeId,
+    /// Name as returned by [`Settings::system_name`]
+    pub name: String,
+    /// Name as returned by [`Settings::full_system_name`]
+    pub full_name: String,
+    pub style: NodeStyle,
+    /// Whether the system requires exclusive `World` access
+    pub exclusive: bool,
+    /// Parent sets, not including the implicit sets of system types
+    pub parents: Vec<NodeId>,
+    pub conditions: Vec<ConditionNode>,
+    pub access: SystemAccess,
+}
+
+/// Components and resources a system reads and writes, from the access of the initialized system
+#[derive(Default, Clone, Debug)]
+pub struct SystemAccess {
+    /// Full type names of the components only read
+    pub components_read: Vec<String>,
+    /// Full type names of the components written
+    pub components_written: Vec<String>,
+    /// Full type names of the resources only read
+    pub resources_read: Vec<String>,
+    /// Full type names of the resources written
+    pub resources_written: Vec<String>,
+    /// Whether the system reads all components or all resources, e.g. with `&World` or `EntityRef`
+    pub reads_all: bool,
+    /// Whether the system writes all components or all resources, e.g. as an exclusive system
+    pub writes_all: bool,
+}
+
+impl SystemAccess {
+    /// Whether the system accesses no data at all
+    pub fn is_empty(&self) -> bool {
+        !self.reads_all
+            && !self.writes_all
+            && self.components_read.is_empty()
+            && self.components_written.is_empty()
+            && self.resources_read.is_empty()
+            && self.resources_written.is_empty()
+    }
+}
+
+pub struct SetNode {
+    pub id: NodeId,
+    /// Name as returned by [`Settings::system_set_name`]
+    pub name: String,
+    pub kind: SetKind,
+    /// Parent sets, not including the implicit sets of system types
+    pub parents: Vec<NodeId>,
+    pub conditions: Vec<ConditionNode>,
+}
+
+/// A run condition of a system or set
+pub struct ConditionNode {
+    /// Full type name of the condition
+    pub name: String,
+    /// Components and resources the condition reads
+    pub access: SystemAccess,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SetKind {
+    /// A set configured by the user
+    Regular,
+    /// The implicit set of `ApplyDeferred`
+    ApplyDeferred,
+    /// The implicit set of a system type, e.g. from `.after(some_system)`.
+    /// `system` is `None` when the system isn't part of the schedule.
+    SystemType { system: Option<NodeId> },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DependencyEdge {
+    pub from: NodeId,
+    pub to: NodeId,
+    /// Whether the ordering is already implied by a longer path.
+    /// Only computed if [`Settings::remove_transitive_edges`] is set or the model was built with
+    /// [`ScheduleModel::with_transitive_flags`], `false` otherwise.
+    pub transitive: bool,
+}
+
+pub struct Ambiguity {
+    pub system_a: NodeId,
+    pub system_b: NodeId,
+    /// Full type names of the conflicting components and resources.
+    /// Empty if the systems conflict on the whole `World`.
+    pub conflicts: Vec<String>,
+}
+
+impl ScheduleModel {
+    pub fn new(schedule: &Schedule, world: &World, settings: &Settings) -> ScheduleModel {
+        Self::build(
+            schedule,
+            world,
+            settings,
+            settings.remove_transitive_edges,
+            true,
+            settings.ambiguity_enable,
+        )
+    }
+
+    /// Like [`ScheduleModel::new`], but with every system and set of the schedule, ignoring
+    /// [`Settings::include_system`], [`Settings::filter`] and [`Settings::focus`].
+    ///
+    /// Orderings through systems and sets that are filtered out are lost in a filtered model,
+    /// so analyses of the ordering search this one, and render their results on the filtered model.
+    pub fn unfiltered(schedule: &Schedule, world: &World, settings: &Settings) -> ScheduleModel {
+        Self::build(
+            schedule,
+            world,
+            settings,
+            settings.remove_transitive_edges,
+            false,
+            settings.ambiguity_enable,
+        )
+    }
+
+    /// Like [`ScheduleModel::new`], but always flags transitive dependency edges, for exports that keep them
+    pub fn with_transitive_flags(
+        schedule: &Schedule,
+        world: &World,
+        settings: &Settings,
+    ) -> ScheduleModel {
+        Self::build(
+            schedule,
+            world,
+            settings,
+            true,
+            true,
+            settings.ambiguity_enable,
+        )
+    }
+
+    /// Like [`ScheduleModel::new`], but always collects ambiguities, for reports on them
+    pub fn with_ambiguities(
+        schedule: &Schedule,
+        world: &World,
+        settings: &Settings,
+    ) -> ScheduleModel {
+        Self::build(
+            schedule,
+            world,
+            settings,
+            settings.remove_transitive_edges,
+            true,
+            true,
+        )
+    }
+
+    fn build(
+        schedule: &Schedule,
+        world: &World,
+        settings: &Settings,
+        flag_transitive: bool,
+        filtered: bool,
+        ambiguities: bool,
+    ) -> ScheduleModel {
+        let graph = schedule.graph();
+        let hierarchy = graph.hierarchy().graph();
+
+        let dependency = graph.dependency().graph();
+        let reduced_dependency = flag_transitive.then(|| {
+            let mut reduced_dependency = dependency.clone();
+            remove_transitive_edges(&mut reduced_dependency);
+            reduced_dependency
+        });
+
+        let included_systems_sets = if filtered {
+            included_systems_sets(graph, settings)
+        } else {
+            all_systems_sets(graph)
+        };
+
+        let systems: Vec<_> = graph
+            .systems
+            .iter()
+            .filter(|(id, ..)| included_systems_sets.contains(&NodeId::System(*id)))
+            .map(|(system_id, system, conditions)| {
+                let id = NodeId::System(system_id);
+                SystemNode {
+                    id,
+                    name: (settings.system_name)(system),
+                    full_name: (settings.full_system_name)(system),
+                    style: settings.get_system_style(system),
+                    exclusive: system.is_exclusive(),
+                    parents: hierarchy_parents(id, graph).collect(),
+                    conditions: condition_nodes(conditions, world),
+                    access: system_access(
+                        &graph.systems.get(system_id).unwrap().access(),
+                        system.is_exclusive(),
+                        world,
+                    ),
+                }
+            })
+            .collect();
+
+        let mut system_sets: Vec<_> = graph
+            .system_sets
+            .iter()
+            .filter(|(id, ..)| included_systems_sets.contains(&NodeId::Set(*id)))
+            .collect();
+        system_sets.sort_by_key(|&(node_id, ..)| node_id);
+
+        let sets: Vec<_> = system_sets
+            .into_iter()
+            .map(|(set_id, set, conditions)| {
+                let id = NodeId::Set(set_id);
+                let kind = match set.system_type() {
+                    None => SetKind::Regular,
+                    Some(type_id) if type_id == TypeId::of::<ApplyDeferred>() => {
+                        SetKind::ApplyDeferred
+                    }
+                    Some(_) => SetKind::SystemType {
+                        system: system_of_system_type(graph, set).map(NodeId::System),
+                    },
+                };
+                SetNode {
+                    id,
+                    name: (settings.system_set_name)(set),
+                    kind,
+                    parents: match kind {
+                        SetKind::Regular => hierarchy_parents(id, graph).collect(),
+                        _ => Vec::new(),
+                    },
+                    conditions: condition_nodes(conditions, world),
+                }
+            })
+            .collect();
+
+        // collect sets and systems
+        let mut systems_freestanding = Vec::new();
+        let mut systems_in_single_set = HashMap::<NodeId, Vec<_>>::default();
+        let mut systems_in_multiple_sets = HashMap::<Option<NodeId>, Vec<_>>::default();
+
+        for system in &systems {
+            match iter_single(system.parents.iter().copied()) {
+                IterSingleResult::Empty => systems_freestanding.push(system.id),
+                IterSingleResult::Single(parent) => {
+                    systems_in_single_set
+                        .entry(parent)
+                        .or_default()
+                        .push(system.id);
+                }
+                IterSingleResult::Multiple(parents) => {
+                    let first_common_ancestor = lowest_common_ancestor(&parents, hierarchy);
+
+                    systems_in_multiple_sets
+                        .entry(first_common_ancestor)
+                        .or_default()
+                        .push(system.id)
+                }
+            }
+        }
+
+        let mut sets_freestanding = Vec::new();
+        let mut sets_in_single_set = HashMap::<NodeId, Vec<_>>::default();
+        let mut sets_in_multiple_sets = HashMap::<Option<NodeId>, Vec<_>>::default();
+
+        for set in sets.iter().filter(|set| set.kind == SetKind::Regular) {
+            match iter_single(set.parents.iter().copied()) {
+                IterSingleResult::Empty => sets_freestanding.push(set.id),
+                IterSingleResult::Single(parent) => {
+                    sets_in_single_set.entry(parent).or_default().push(set.id);
+                }
+                IterSingleResult::Multiple(parents) => {
+                    let first_common_ancestor = lowest_common_ancestor(&parents, hierarchy);
+
+                    sets_in_multiple_sets
+                        .entry(first_common_ancestor)
+                        .or_default()
+                        .push(set.id);
+                }
+            }
+        }
+
+        let mut collapsed_sets = HashSet::default();
+        let mut collapsed_set_children = HashMap::default();
+
+        if settings.collapse_single_system_sets {
+            for set in sets.iter().filter(|set| set.kind == SetKind::Regular) {
+                let node_id = set.id;
+                let children = systems_in_single_set
+                    .get(&node_id)
+                    .map_or(&[] as &[_], |vec| vec.as_slice());
+                let children_in_multiple = systems_in_multiple_sets
+                    .get(&Some(node_id))
+                    .map_or(&[] as &[_], |vec| vec.as_slice());
+
+                let children_sets_empty = sets_in_single_set
+                    .get(&node_id)
+                    .is_none_or(|vec| vec.is_empty());
+                let children_sets_in_multiple_empty = systems_in_multiple_sets
+                    .get(&Some(node_id))
+                    .is_none_or(|vec| vec.is_empty());
+
+                if children_in_multiple.is_empty()
+                    && children.len() <= 1
+                    && children_sets_empty
+                    && children_sets_in_multiple_empty
+                {
+                    collapsed_sets.insert(node_id);
+
+                    for &child in children {
+                        collapsed_set_children.insert(child, node_id);
+                    }
+                    for &child in children_in_multiple {
+                        collapsed_set_children.insert(child, node_id);
+                    }
+                }
+            }
+        }
+
+        let dependencies = dependency
+            .all_edges()
+            .filter(|(from, to)| {
+                included_systems_sets.contains(from) && included_systems_sets.contains(to)
+            })
+            .map(|(from, to)| DependencyEdge {
+                from,
+                to,
+                transitive: reduced_dependency
+                    .as_ref()
+                    .is_some_and(|reduced| !reduced.contains_edge(from, to)),
+            })
+            .collect();
+
+        let ambiguities = if ambiguities {
+            collect_ambiguities(graph, world, settings, &included_systems_sets)
+        } else {
+            Vec::new()
+        };
+
+        let system_indices = systems
+            .iter()
+            .enumerate()
+            .map(|(i, system)| (system.id, i))
+            .collect();
+        let set_indices = sets
+            .iter()
+            .enumerate()
+            .map(|(i, set)| (set.id, i))
+            .collect();
+
+        ScheduleModel {
+            label: format!("{:?}", schedule.label()),
+            systems,
+            sets,
+            dependencies,
+            ambiguities,
+            systems_freestanding,
+            systems_in_single_set,
+            systems_in_multiple_sets,
+            sets_freestanding,
+            sets_in_single_set,
+            sets_in_multiple_sets,
+            collapsed_sets,
+            collapsed_set_children,
+            remove_transitive_edges: settings.remove_transitive_edges,
+            system_indices,
+            set_indices,
+        }
+    }
+
+    /// Dependency edges to draw, leaving out transitive ones if [`Settings::remove_transitive_edges`] is set
+    pub fn drawn_dependencies(&self) -> impl Iterator<Item = &DependencyEdge> {
+        self.dependencies
+            .iter()
+            .filter(|edge| !(self.remove_transitive_edges && edge.transitive))
+    }
+
+    /// Whether the system or set is part of the model
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.system_indices.contains_key(&id) || self.set_indices.contains_key(&id)
+    }
+
+    pub fn system(&self, id: NodeId) -> Option<&SystemNode> {
+        self.system_indices.get(&id).map(|&i| &self.systems[i])
+    }
+
+    pub fn set(&self, id: NodeId) -> Option<&SetNode> {
+        self.set_indices.get(&id).map(|&i| &self.sets[i])
+    }
+
+    /// Name of the system or set, as given by the [`Settings`] the model was built with
+    pub fn node_name(&self, id: NodeId) -> &str {
+        match id {
+            NodeId::System(_) => &self.system(id).unwrap().name,
+            NodeId::Set(_) => &self.set(id).unwrap().name,
+        }
+    }
+
+    /// Edges from parent set to child, for all included systems and sets
+    pub fn hierarchy(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        let systems = self
+            .systems
+            .iter()
+            .flat_map(|system| system.parents.iter().map(|&parent| (parent, system.id)));
+        let sets = self
+            .sets
+            .iter()
+            .flat_map(|set| set.parents.iter().map(|&parent| (parent, set.id)));
+        systems.chain(sets)
+    }
+
+    /// Parents and children of every node, including the implicit sets of system types
+    pub(crate) fn parents_and_children(
+        &self,
+    ) -> (HashMap<NodeId, Vec<NodeId>>, HashMap<NodeId, Vec<NodeId>>) {
+        let mut parents = HashMap::<NodeId, Vec<NodeId>>::default();
+        let mut children = HashMap::<NodeId, Vec<NodeId>>::default();
+        let system_type_sets = self.sets.iter().filter_map(|set| match set.kind {
+            SetKind::SystemType {
+                system: Some(system),
+            } => Some((set.id, system)),
+            _ => None,
+        });
+        for (parent, child) in self.hierarchy().chain(system_type_sets) {
+            parents.entry(child).or_default().push(parent);
+            children.entry(parent).or_default().push(child);
+        }
+        (parents, children)
+    }
+
+    /// Systems whose only parent is `set`
+    pub fn systems_in_single_set(&self, set: NodeId) -> &[NodeId] {
+        self.systems_in_single_set
+            .get(&set)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Systems in multiple sets whose lowest common ancestor is `set`
+    pub fn systems_in_multiple_sets(&self, set: Option<NodeId>) -> &[NodeId] {
+        self.systems_in_multiple_sets
+            .get(&set)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Sets whose only parent is `set`
+    pub fn sets_in_single_set(&self, set: NodeId) -> &[NodeId] {
+        self.sets_in_single_set
+            .get(&set)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Sets in multiple sets whose lowest common ancestor is `set`
+    pub fn sets_in_multiple_sets(&self, set: Option<NodeId>) -> &[NodeId] {
+        self.sets_in_multiple_sets
+            .get(&set)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+fn condition_nodes(conditions: &[ConditionWithAccess], world: &World) -> Vec<ConditionNode> {
+    conditions
+        .iter()
+        .map(|condition| ConditionNode {
+            name: condition.name().to_string(),
+            access: system_access(&condition.access(), false, world),
+        })
+        .collect()
+}
+
+fn system_access(access: &FilteredAccessSet, exclusive: bool, world: &World) -> SystemAccess {
+    let access = access.combined_access();
+    let name = |id: ComponentId| {
+        world
+            .components()
+            .get_info(id)
+            .map_or_else(|| format!("{id:?}"), |info| info.name().to_string())
+    };
+
+    let mut system_access = SystemAccess {
+        reads_all: access.has_read_all_resources(),
+        writes_all: exclusive || access.has_write_all_resources(),
+        ..Default::default()
+    };
+    if !system_access.reads_all {
+        system_access.resources_read = access.resource_reads().map(name).collect();
+        system_access.resources_written = access.resource_writes().map(name).collect();
+    }
+    match access.try_iter_component_access() {
+        Ok(components) => {
+            for kind in components {
+                match kind {
+                    ComponentAccessKind::Shared(id) => system_access.components_read.push(name(id)),
+                    ComponentAccessKind::Exclusive(id) => {
+                        system_access.components_written.push(name(id))
+                    }
+                    // only the presence of the component is checked, e.g. with `Has<T>`
+                    ComponentAccessKind::Archetypal(_) => {}
+                }
+            }
+        }
+        Err(unbounded) => {
+            system_access.reads_all = true;
+            system_access.writes_all |= unbounded.writes_inverted;
+        }
+    }
+
+    for names in [
+        &mut system_access.components_read,
+        &mut system_access.components_written,
+        &mut system_access.resources_read,
+        &mut system_access.resources_written,
+    ] {
+        names.sort();
+        names.dedup();
+    }
+    system_access
+}
+
+fn collect_ambiguities(
+    graph: &ScheduleGraph,
+    world: &World,
+    settings: &Settings,
+    included_systems_sets: &HashSet<NodeId>,
+) -> Vec<Ambiguity> {
+    let mut conflicting_systems = graph.conflicting_systems().to_vec();
+    conflicting_systems.sort();
+
+    let mut ambiguities = Vec::new();
+    for (system_a, system_b, conflicts) in conflicting_systems {
+        if !included_systems_sets.contains(&NodeId::System(system_a))
+            || !included_systems_sets.contains(&NodeId::System(system_b))
+        {
+            continue;
+        }
+
+        if conflicts.is_empty() && !settings.ambiguity_enable_on_world {
+            continue;
+        }
+
+        if let Some(include_ambiguity) = &settings.include_ambiguity {
+            let a = graph.systems.get(system_a).unwrap();
+            let b = graph.systems.get(system_b).unwrap();
+            if !include_ambiguity(&a.system(), &b.system(), &conflicts, world) {
+                continue;
+            }
+        }
+
+        let conflicts = conflicts
+            .iter()
+            .map(|&component_id| {
+                world
+                    .components()
+                    .get_info(component_id)
+                    .unwrap()
+                    .name()
+                    .to_string()
+            })
+            .collect();
+
+        ambiguities.push(Ambiguity {
+            system_a: NodeId::System(system_a),
+            system_b: NodeId::System(system_b),
+            conflicts,
+        });
+    }
+    ambiguities
+}
+
+// PERF: O(n)
+fn system_of_system_type(graph: &ScheduleGraph, set: &dyn SystemSet) -> Option<SystemKey> {
+    graph.systems.iter().find_map(|(id, system, _)| {
+        let is_system_set = system.default_system_sets().iter().any(|s| s.0 == set);
+        is_system_set.then_some(id)
+    })
+}
+
+/// Builds a schedule of `systems` in a new world for tests, leaving the systems in the graph like
+/// `with_initialized_schedule` does. `Schedule::initialize` would move them out.
+#[cfg(test)]
+pub(crate) fn test_schedule<M>(
+    systems: impl bevy_ecs::schedule::IntoScheduleConfigs<bevy_ecs::system::ScheduleSystem, M>,
+) -> (Schedule, World) {
+    let mut world = World::new();
+    let mut schedule = Schedule::default();
+    schedule.add_systems(systems);
+    schedule.graph_mut().initialize(&mut world);
+    let _ = schedule
+        .graph_mut()
+        .build_schedule(&mut world, &Default::default());
+    (schedule, world)
+}
@@ -0,0 +1,445 @@
+//! Overview of the schedules an app and its sub-apps run, in the order they run them.
+
+use std::fmt::Write;
+
+use bevy_app::{
+    App, FixedMain, FixedMainScheduleOrder, Main, MainScheduleOrder, RunFixedMainLoop, SubApp,
+};
+use bevy_ecs::{
+    intern::Interned,
+    schedule::{ScheduleLabel, Schedules},
+    world::World,
+};
+use bevy_platform::collections::hash_set::HashSet;
+
+use crate::dot::DotGraph;
+
+use super::Settings;
+
+/// The schedule the render sub-app runs before its update, to extract data from the main world
+const EXTRACT_SCHEDULE: &str = "ExtractSchedule";
+
+/// How a schedule is run by the schedule containing it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunKind {
+    /// Run by the app itself, manually or by systems
+    Root,
+    /// Once, on the first run of the parent
+    Startup,
+    /// On every run of the parent
+    EveryRun,
+    /// As often as needed to catch up with the fixed timestep, which may be zero times per run of the parent
+    FixedTimestep,
+}
+
+/// A schedule and the schedules it runs
+pub struct ScheduleRun {
+    /// `Debug` name of the schedule label
+    pub name: String,
+    /// Number of systems, or `None` if the app doesn't have the schedule
+    pub system_count: Option<usize>,
+    pub kind: RunKind,
+    /// Schedules run by this schedule, in order
+    pub runs: Vec<ScheduleRun>,
+}
+
+/// The schedules of the main app or of a sub-app
+pub struct AppSchedules {
+    /// `Debug` name of the sub-app label, or `None` for the main app
+    pub sub_app: Option<String>,
+    /// The `ExtractSchedule` of a sub-app, which runs before its update
+    pub extract: Option<ScheduleRun>,
+    /// The schedule run on every update of the app
+    pub update: Option<ScheduleRun>,
+    /// Schedules that aren't run by the update schedule but manually or by systems, sorted by name
+    pub other: Vec<ScheduleRun>,
+}
+
+/// Every schedule of an app and its sub-apps, in the order they run.
+///
+/// The main app's `Main` schedule runs the schedules of [`MainScheduleOrder`], `RunFixedMainLoop` runs `FixedMain`,
+/// which runs the schedules of [`FixedMainScheduleOrder`]. After the main app, every sub-app extracts from the main world and updates.
+pub struct AppOverview {
+    /// The main app, followed by the sub-apps sorted by name
+    pub apps: Vec<AppSchedules>,
+}
+
+impl AppOverview {
+    pub fn new(app: &App) -> AppOverview {
+        let mut sub_apps: Vec<_> = app
+            .sub_apps()
+            .sub_apps
+            .iter()
+            .map(|(label, sub_app)| (format!("{label:?}"), sub_app))
+            .collect();
+        sub_apps.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut apps = vec![AppSchedules::new(None, &app.sub_apps().main)];
+        apps.extend(
+            sub_apps
+                .into_iter()
+                .map(|(name, sub_app)| AppSchedules::new(Some(name), sub_app)),
+        );
+        AppOverview { apps }
+    }
+
+    /// Lists the schedules of every app as an indented tree, with their number of systems
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for app in &self.apps {
+            let _ = writeln!(out, "{}", app.sub_app.as_deref().unwrap_or("Main app"));
+            for run in app.extract.iter().chain(&app.update) {
+                write_run(&mut out, run, 1);
+            }
+            if !app.other.is_empty() {
+                let _ = writeln!(out, "  Other schedules, run manually or by systems");
+                for run in &app.other {
+                    write_run(&mut out, run, 2);
+                }
+            }
+        }
+        out
+    }
+
+    /// Draws every app as a cluster, with schedules that run other schedules as clusters containing them in order.
+    ///
+    /// `link` is called with the sub-app name (`None` for the main app) and the schedule name of every schedule the app has,
+    /// and returns the URL of its detailed diagram, e.g. `schedule_Update.dot.svg`.
+    pub fn to_dot(
+        &self,
+        settings: &Settings,
+        link: impl Fn(Option<&str>, &str) -> Option<String>,
+    ) -> String {
+        let style = &settings.style;
+        let mut dot = DotGraph::new(
+            "",
+            "digraph",
+            &[
+                ("compound", "true"),
+                ("splines", style.edge_style.as_dot()),
+                ("rankdir", style.schedule_rankdir.as_dot()),
+                ("bgcolor", &style.color_background),
+                ("fontname", &style.fontname),
+                ("nodesep", "0.15"),
+            ],
+        )
+        .edge_attributes(&[
+            ("penwidth", &format!("{}", style.penwidth_edge)),
+            ("color", &style.color_edge[0]),
+            ("fontcolor", &style.color_edge[0]),
+            ("fontname", &style.fontname),
+        ])
+        .node_attributes(&[
+            ("shape", "box"),
+            ("style", "filled"),
+            ("fontname", &style.fontname),
+        ]);
+
+        let mut graph = OverviewGraph {
+            settings,
+            link,
+            next_id: 0,
+            edges: Vec::new(),
+        };
+
+        let mut main_update = None;
+        for (i, app) in self.apps.iter().enumerate() {
+            let sub_app = app.sub_app.as_deref();
+            let name = sub_app.unwrap_or("Main app");
+            let mut app_graph = DotGraph::subgraph(
+                &format!("app_{i}"),
+                &[
+                    ("style", "rounded"),
+                    ("label", name),
+                    ("tooltip", name),
+                    ("fontcolor", &style.color_set_label),
+                    ("color", &style.color_set_border),
+                    ("penwidth", "2"),
+                ],
+            );
+
+            let extract = app
+                .extract
+                .as_ref()
+                .map(|run| graph.add_run(&mut app_graph, sub_app, run));
+            let update = app
+                .update
+                .as_ref()
+                .map(|run| graph.add_run(&mut app_graph, sub_app, run));
+            if let Some(extract) = &extract {
+                if let Some(update) = &update {
+                    graph.edge(extract, update, "");
+                }
+            }
+            if sub_app.is_none() {
+                main_update = update.clone();
+            } else if let Some(main_update) = &main_update {
+                match (&extract, &update) {
+                    (Some(first), _) => graph.edge(main_update, first, "extract"),
+                    (None, Some(first)) => graph.edge(main_update, first, "then"),
+                    (None, None) => {}
+                }
+            }
+
+            if !app.other.is_empty() {
+                let label = "other schedules, run manually or by systems";
+                let mut other_graph = DotGraph::subgraph(
+                    &format!("app_{i}_other"),
+                    &[
+                        ("style", "rounded,dashed"),
+                        ("label", label),
+                        ("tooltip", label),
+                        ("fontcolor", &style.color_set_label),
+                        ("color", &style.color_set_border),
+                    ],
+                );
+                for run in &app.other {
+                    graph.add_run(&mut other_graph, sub_app, run);
+                }
+                app_graph.add_sub_graph(other_graph);
+            }
+
+            dot.add_sub_graph(app_graph);
+        }
+
+        // edges are added to the top level, since nodes mentioned in a subgraph are drawn inside of it
+        for (from, to, label) in &graph.edges {
+            let mut attrs = vec![("label", label.as_str())];
+            if let Some(cluster) = &from.cluster {
+                attrs.push(("ltail", cluster));
+            }
+            if let Some(cluster) = &to.cluster {
+                attrs.push(("lhead", cluster));
+            }
+            dot.add_edge(&from.node, &to.node, &attrs);
+        }
+
+        dot.finish()
+    }
+}
+
+impl AppSchedules {
+    fn new(sub_app: Option<String>, app: &SubApp) -> AppSchedules {
+        let world = app.world();
+        let mut expanded = HashSet::default();
+
+        let update = app
+            .update_schedule
+            .map(|label| schedule_run(world, label, RunKind::Root, &mut expanded));
+
+        let mut labels: Vec<_> = world
+            .get_resource::<Schedules>()
+            .into_iter()
+            .flat_map(|schedules| schedules.iter())
+            .map(|(_, schedule)| (format!("{:?}", schedule.label()), schedule.label()))
+            .collect();
+        labels.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let mut extract = None;
+        let mut other = Vec::new();
+        for (name, label) in labels {
+            if expanded.contains(&label) {
+                continue;
+            }
+            let run = schedule_run(world, label, RunKind::Root, &mut expanded);
+            if sub_app.is_some() && name == EXTRACT_SCHEDULE {
+                extract = Some(run);
+            } else {
+                other.push(run);
+            }
+        }
+
+        AppSchedules {
+            sub_app,
+            extract,
+            update,
+            other,
+        }
+    }
+}
+
+impl ScheduleRun {
+    fn system_count_text(&self) -> String {
+        match self.system_count {
+            None => "not in the app".to_owned(),
+            Some(1) => "1 system".to_owned(),
+            Some(count) => format!("{count} systems"),
+        }
+    }
+}
+
+/// The schedule and the schedules it runs. Schedules already in `expanded` are not expanded again, which guards against cycles.
+fn schedule_run(
+    world: &World,
+    label: Interned<dyn ScheduleLabel>,
+    kind: RunKind,
+    expanded: &mut HashSet<Interned<dyn ScheduleLabel>>,
+) -> ScheduleRun {
+    let system_count = world
+        .get_resource::<Schedules>()
+        .and_then(|schedules| schedules.get(label))
+        .map(|schedule| schedule.systems_len());
+
+    let mut runs = Vec::new();
+    if expanded.insert(label) {
+        if label == Main.intern() {
+            if let Some(order) = world.get_resource::<MainScheduleOrder>() {
+                for &startup in &order.startup_labels {
+                    runs.push(schedule_run(world, startup, RunKind::Startup, expanded));
+                }
+                for &label in &order.labels {
+                    runs.push(schedule_run(world, label, RunKind::EveryRun, expanded));
+                }
+            }
+        } else if label == RunFixedMainLoop.intern() {
+            let fixed_main = FixedMain.intern();
+            runs.push(schedule_run(
+                world,
+                fixed_main,
+                RunKind::FixedTimestep,
+                expanded,
+            ));
+        } else if label == FixedMain.intern() {
+            if let Some(order) = world.get_resource::<FixedMainScheduleOrder>() {
+                for &label in &order.labels {
+                    runs.push(schedule_run(world, label, RunKind::EveryRun, expanded));
+                }
+            }
+        }
+    }
+
+    ScheduleRun {
+        name: format!("{label:?}"),
+        system_count,
+        kind,
+        runs,
+    }
+}
+
+fn write_run(out: &mut String, run: &ScheduleRun, depth: usize) {
+    let when = match run.kind {
+        RunKind::Root | RunKind::EveryRun => "",
+        RunKind::Startup => ", once at startup",
+        RunKind::FixedTimestep => ", on a fixed timestep",
+    };
+    let _ = writeln!(
+        out,
+        "{:indent$}{}: {}{when}",
+        "",
+        run.name,
+        run.system_count_text(),
+        indent = depth * 2
+    );
+    for child in &run.runs {
+        write_run(out, child, depth + 1);
+    }
+}
+
+/// A schedule node, or the invisible node of a schedule cluster with the cluster name for `lhead` and `ltail`
+#[derive(Clone)]
+struct Endpoint {
+    node: String,
+    cluster: Option<String>,
+}
+
+struct OverviewGraph<'a, L> {
+    settings: &'a Settings,
+    link: L,
+    next_id: usize,
+    edges: Vec<(Endpoint, Endpoint, String)>,
+}
+
+impl<L: Fn(Option<&str>, &str) -> Option<String>> OverviewGraph<'_, L> {
+    fn edge(&mut self, from: &Endpoint, to: &Endpoint, label: &str) {
+        self.edges
+            .push((from.clone(), to.clone(), label.to_owned()));
+    }
+
+    /// Adds the schedule as a node, or as a cluster if it runs other schedules, chaining them in the order they run
+    fn add_run(
+        &mut self,
+        dot: &mut DotGraph,
+        sub_app: Option<&str>,
+        run: &ScheduleRun,
+    ) -> Endpoint {
+        let style = &self.settings.style;
+        let id = format!("schedule_{}", self.next_id);
+        self.next_id += 1;
+
+        // escaped line breaks, since nested subgraphs indent every line of the output
+        let mut label = format!("{}\\n{}", run.name, run.system_count_text());
+        if run.kind == RunKind::FixedTimestep {
+            label.push_str("\\non a fixed timestep");
+        }
+        let url = run
+            .system_count
+            .and_then(|_| (self.link)(sub_app, &run.name));
+        let mut attrs = vec![
+            ("label", label.as_str()),
+            ("tooltip", run.name.as_str()),
+            ("fillcolor", &style.color_set),
+            ("fontcolor", &style.color_set_label),
+        ];
+        if let Some(url) = &url {
+            attrs.push(("URL", url));
+        }
+
+        if run.runs.is_empty() {
+            attrs.push(("color", &style.color_set_border));
+            dot.add_node(&id, &attrs);
+            return Endpoint {
+                node: id,
+                cluster: None,
+            };
+        }
+
+        attrs.extend([
+            ("style", "rounded,filled"),
+            ("color", &style.color_set_border),
+            ("penwidth", "2"),
+        ]);
+        let mut graph = DotGraph::subgraph(&id, &attrs);
+        graph.add_invisible_node(&id);
+
+        let mut previous: Option<Endpoint> = None;
+        let (startup, every_run): (Vec<_>, Vec<_>) = run
+            .runs
+            .iter()
+            .partition(|child| child.kind == RunKind::Startup);
+        if !startup.is_empty() {
+            let label = "once at startup";
+            let mut startup_graph = DotGraph::subgraph(
+                &format!("{id}_startup"),
+                &[
+                    ("style", "rounded,dashed"),
+                    ("label", label),
+                    ("tooltip", label),
+                    ("fontcolor", &style.color_set_label),
+                    ("color", &style.color_set_border),
+                ],
+            );
+            for child in startup {
+                let child = self.add_run(&mut startup_graph, sub_app, child);
+                self.chain(&mut previous, child);
+            }
+            graph.add_sub_graph(startup_graph);
+        }
+        for child in every_run {
+            let child = self.add_run(&mut graph, sub_app, child);
+            self.chain(&mut previous, child);
+        }
+
+        dot.add_sub_graph(graph);
+        Endpoint {
+            cluster: Some(format!("cluster{id}")),
+            node: id,
+        }
+    }
+
+    fn chain(&mut self, previous: &mut Option<Endpoint>, next: Endpoint) {
+        if let Some(previous) = previous {
+            self.edge(previous, &next, "");
+        }
+        *previous = Some(next);
+    }
+}
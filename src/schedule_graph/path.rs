@@ -0,0 +1,379 @@
+//! Ordering paths between two systems, to explain why one runs after the other.
+
+use std::fmt::Write;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::{hash_map::HashMap, hash_set::HashSet};
+
+use super::{model::ScheduleModel, Highlight, Settings};
+
+/// Whether the first system runs before or after the second one
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PathOrder {
+    Before,
+    After,
+    /// Neither has to run before the other
+    Unordered,
+}
+
+/// A step of an ordering path
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PathStep {
+    /// The system the path starts at
+    Start(NodeId),
+    /// The previous node is in this set, so the orderings of the set apply to it
+    InSet(NodeId),
+    /// The previous node runs before this one
+    Before(NodeId),
+    /// The previous set contains this node, so it runs after everything the set runs after
+    Contains(NodeId),
+}
+
+impl PathStep {
+    pub fn node(self) -> NodeId {
+        match self {
+            PathStep::Start(node)
+            | PathStep::InSet(node)
+            | PathStep::Before(node)
+            | PathStep::Contains(node) => node,
+        }
+    }
+}
+
+/// Every ordering path between two systems, counting paths through sets.
+///
+/// A path follows dependency edges, goes up to the sets a node is in, whose orderings apply to it,
+/// and down into the systems of a set that something runs before.
+pub struct DependencyPaths {
+    pub first: NodeId,
+    pub second: NodeId,
+    pub order: PathOrder,
+    /// Number of distinct paths from the earlier to the later system
+    pub count: usize,
+    /// One of the shortest paths from the earlier to the later system
+    pub shortest: Vec<PathStep>,
+    /// Systems and sets on any path
+    pub nodes: HashSet<NodeId>,
+    /// Dependency edges on any path
+    pub dependencies: HashSet<(NodeId, NodeId)>,
+
+    names: HashMap<NodeId, String>,
+}
+
+/// How the path search reached a node
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Reached {
+    /// By going up to a set, or at the start
+    Up,
+    /// By a dependency edge
+    Before,
+    /// By going down from this set
+    Down(NodeId),
+}
+
+/// State of the path search
+type State = (NodeId, Reached);
+
+impl DependencyPaths {
+    /// Finds the paths between the systems named `first` and `second`, by name or full name.
+    ///
+    /// The model should be [unfiltered](ScheduleModel::unfiltered), paths through systems and sets missing from it aren't found.
+    pub fn between(
+        model: &ScheduleModel,
+        first: &str,
+        second: &str,
+    ) -> Result<DependencyPaths, FindSystemError> {
+        let first_id = find_system(model, first)?;
+        let second_id = find_system(model, second)?;
+        if first_id == second_id {
+            return Err(FindSystemError::SameSystem(second.to_owned()));
+        }
+        Ok(DependencyPaths::new(model, first_id, second_id))
+    }
+
+    /// Finds the paths between two systems of the model, see [`DependencyPaths::between`].
+    /// The path from a system to itself is just that system.
+    pub fn new(model: &ScheduleModel, first: NodeId, second: NodeId) -> DependencyPaths {
+        let search = PathSearch::new(model);
+
+        let (order, from, to) = if search.count_paths(first, second) > 0 {
+            (PathOrder::Before, first, second)
+        } else if search.count_paths(second, first) > 0 {
+            (PathOrder::After, second, first)
+        } else {
+            (PathOrder::Unordered, first, second)
+        };
+
+        let mut paths = DependencyPaths {
+            first,
+            second,
+            order,
+            count: 0,
+            shortest: Vec::new(),
+            nodes: HashSet::default(),
+            dependencies: HashSet::default(),
+            names: HashMap::default(),
+        };
+        if order != PathOrder::Unordered {
+            search.collect(from, to, &mut paths);
+        }
+        for node in [first, second]
+            .into_iter()
+            .chain(paths.nodes.iter().copied())
+        {
+            paths.names.insert(node, model.node_name(node).to_owned());
+        }
+        paths
+    }
+
+    /// The systems, sets and dependencies on the paths, to render with [`schedule_model_dot_highlighted`](super::schedule_model_dot_highlighted)
+    pub fn highlight(&self, settings: &Settings) -> Highlight {
+        Highlight {
+            nodes: self.nodes.clone(),
+            dependencies: self.dependencies.clone(),
+            color: settings.style.color_highlight.clone(),
+        }
+    }
+
+    /// Explains the ordering with the shortest path
+    pub fn to_text(&self) -> String {
+        let name = |node: NodeId| self.names[&node].as_str();
+        let (first, second) = (name(self.first), name(self.second));
+
+        let mut out = String::new();
+        let (earlier, later) = match self.order {
+            PathOrder::Unordered => {
+                let _ = writeln!(
+                    out,
+                    "{first} and {second} are unordered, neither has to run before the other"
+                );
+                return out;
+            }
+            PathOrder::Before => (first, second),
+            PathOrder::After => (second, first),
+        };
+        let paths = if self.count == 1 { "path" } else { "paths" };
+        let _ = writeln!(
+            out,
+            "{earlier} runs before {later}, through {} ordering {paths}. Shortest path:",
+            self.count
+        );
+        for &step in &self.shortest {
+            let _ = match step {
+                PathStep::Start(node) => writeln!(out, "  {}", name(node)),
+                PathStep::InSet(set) => writeln!(out, "    in set {}", name(set)),
+                PathStep::Before(node) => writeln!(out, "    runs before {}", name(node)),
+                PathStep::Contains(node) => writeln!(out, "    which contains {}", name(node)),
+            };
+        }
+        out
+    }
+}
+
+struct PathSearch {
+    parents: HashMap<NodeId, Vec<NodeId>>,
+    children: HashMap<NodeId, Vec<NodeId>>,
+    successors: HashMap<NodeId, Vec<NodeId>>,
+}
+
+impl PathSearch {
+    fn new(model: &ScheduleModel) -> Self {
+        let (parents, children) = model.parents_and_children();
+
+        let mut successors = HashMap::<NodeId, Vec<NodeId>>::default();
+        for edge in &model.dependencies {
+            successors.entry(edge.from).or_default().push(edge.to);
+        }
+
+        PathSearch {
+            parents,
+            children,
+            successors,
+        }
+    }
+
+    /// Steps to the next states. Going down into a set right after going up would order
+    /// unrelated systems of the same set, so it needs a dependency edge in between.
+    /// Going back up to the set a node was reached from would only repeat the path.
+    fn next(&self, (node, reached): State) -> impl Iterator<Item = (PathStep, State)> + '_ {
+        let up = neighbors(&self.parents, node)
+            .iter()
+            .filter(move |&&set| reached != Reached::Down(set))
+            .map(|&set| (PathStep::InSet(set), (set, Reached::Up)));
+        let before = neighbors(&self.successors, node)
+            .iter()
+            .map(|&next| (PathStep::Before(next), (next, Reached::Before)));
+        let down = neighbors(&self.children, node)
+            .iter()
+            .filter(move |_| reached != Reached::Up)
+            .map(move |&child| (PathStep::Contains(child), (child, Reached::Down(node))));
+        up.chain(before).chain(down)
+    }
+
+    fn count_paths(&self, from: NodeId, to: NodeId) -> usize {
+        let mut counts = HashMap::default();
+        self.count_from((from, Reached::Up), to, &mut counts)
+    }
+
+    /// Number of paths from `state` to `to`, memoized in `counts`
+    fn count_from(&self, state: State, to: NodeId, counts: &mut HashMap<State, usize>) -> usize {
+        if state.0 == to {
+            return 1;
+        }
+        if let Some(&count) = counts.get(&state) {
+            return count;
+        }
+        // guards against cycles, which the schedule wouldn't build with
+        counts.insert(state, 0);
+        let count = self
+            .next(state)
+            .map(|(_, next)| self.count_from(next, to, counts))
+            .fold(0usize, usize::saturating_add);
+        counts.insert(state, count);
+        count
+    }
+
+    /// Collects the nodes and edges on all paths and a shortest path
+    fn collect(&self, from: NodeId, to: NodeId, paths: &mut DependencyPaths) {
+        let mut counts = HashMap::default();
+        let start = (from, Reached::Up);
+        paths.count = self.count_from(start, to, &mut counts);
+        let on_path = |state: State, counts: &HashMap<State, usize>| {
+            state.0 == to || counts.get(&state).is_some_and(|&count| count > 0)
+        };
+
+        // breadth-first over the states on a path, so the first time `to` is reached is a shortest path
+        let mut previous = HashMap::<State, (State, PathStep)>::default();
+        let mut visited = HashSet::<State>::default();
+        let mut queue = std::collections::VecDeque::from([start]);
+        visited.insert(start);
+        paths.nodes.insert(from);
+        while let Some(state) = queue.pop_front() {
+            if state.0 == to {
+                continue;
+            }
+            for (step, next) in self.next(state) {
+                if !on_path(next, &counts) {
+                    continue;
+                }
+                paths.nodes.insert(next.0);
+                if let PathStep::Before(node) = step {
+                    paths.dependencies.insert((state.0, node));
+                }
+                if visited.insert(next) {
+                    previous.insert(next, (state, step));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut state = previous
+            .keys()
+            .copied()
+            .filter(|state| state.0 == to)
+            .min_by_key(|&state| path_len(state, &previous))
+            // `from == to`, the path is just the start
+            .unwrap_or(start);
+        let mut shortest = Vec::new();
+        while let Some(&(prev, step)) = previous.get(&state) {
+            shortest.push(step);
+            state = prev;
+        }
+        shortest.push(PathStep::Start(from));
+        shortest.reverse();
+        paths.shortest = shortest;
+    }
+}
+
+pub(super) fn neighbors(map: &HashMap<NodeId, Vec<NodeId>>, node: NodeId) -> &[NodeId] {
+    map.get(&node).map(Vec::as_slice).unwrap_or_default()
+}
+
+fn path_len(mut state: State, previous: &HashMap<State, (State, PathStep)>) -> usize {
+    let mut len = 0;
+    while let Some(&(prev, _)) = previous.get(&state) {
+        state = prev;
+        len += 1;
+    }
+    len
+}
+
+/// Looks up a system of the model by its name or full name, ignoring case
+pub fn find_system(model: &ScheduleModel, name: &str) -> Result<NodeId, FindSystemError> {
+    let lower_name = name.to_lowercase();
+    let mut matches = model.systems.iter().filter(|system| {
+        system.name.to_lowercase() == lower_name || system.full_name.to_lowercase() == lower_name
+    });
+    match (matches.next(), matches.next()) {
+        (Some(system), None) => Ok(system.id),
+        (Some(_), Some(_)) => Err(FindSystemError::MoreThanOneMatch(name.to_owned())),
+        (None, _) => Err(FindSystemError::NoMatch(name.to_owned())),
+    }
+}
+
+#[derive(Debug)]
+pub enum FindSystemError {
+    NoMatch(String),
+    /// Multiple systems have this name, use the full name instead
+    MoreThanOneMatch(String),
+    /// Both names refer to this system
+    SameSystem(String),
+}
+
+impl std::fmt::Display for FindSystemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FindSystemError::NoMatch(name) => write!(f, "no system named '{name}'"),
+            FindSystemError::MoreThanOneMatch(name) => write!(
+                f,
+                "more than one system named '{name}', use the full name instead"
+            ),
+            FindSystemError::SameSystem(name) => {
+                write!(f, "both names refer to the same system '{name}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FindSystemError {}
+
+#[test]
+fn dependency_paths() {
+    use bevy_ecs::prelude::*;
+
+    use super::{filter::Filter, settings::FocusDirection};
+
+    fn a() {}
+    fn b() {}
+    fn c() {}
+
+    let (schedule, world) = super::model::test_schedule((a, b, c).chain());
+
+    // only `a` and `c` are drawn, but `b` still orders them
+    let settings = Settings::default().focus_on(
+        Filter::system("*::a").or(Filter::system("*::c")),
+        0,
+        FocusDirection::Both,
+    );
+    let filtered = ScheduleModel::new(&schedule, &world, &settings);
+    assert!(!filtered.systems.iter().any(|system| system.name == "b"));
+
+    let model = ScheduleModel::unfiltered(&schedule, &world, &settings);
+    let paths = DependencyPaths::between(&model, "c", "a").unwrap();
+    assert_eq!(paths.order, PathOrder::After);
+    assert_eq!(paths.count, 1);
+    let shortest: Vec<_> = (paths.shortest.iter())
+        .map(|step| model.node_name(step.node()))
+        .collect();
+    assert_eq!(shortest, ["a", "b", "c"]);
+
+    assert!(matches!(
+        DependencyPaths::between(&model, "a", "a"),
+        Err(FindSystemError::SameSystem(_))
+    ));
+    let a = find_system(&model, "a").unwrap();
+    assert_eq!(
+        DependencyPaths::new(&model, a, a).shortest,
+        [PathStep::Start(a)]
+    );
+}
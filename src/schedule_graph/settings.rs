@@ -1,11 +1,14 @@
-use std::any::TypeId;
+use std::{any::TypeId, str::FromStr};
 
 use bevy_color::{Color, Hsla};
 use bevy_ecs::{component::ComponentId, schedule::SystemSet, system::ScheduleSystem, world::World};
 
-use super::system_style::{color_to_hex, system_to_style, SystemStyle};
+use super::{
+    filter::Filter,
+    system_style::{color_to_hex, system_to_style, SystemStyle},
+};
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Debug)]
 pub enum RankDir {
     TopDown,
     #[default]
@@ -19,8 +22,20 @@ impl RankDir {
         }
     }
 }
+impl FromStr for RankDir {
+    type Err = String;
+
+    /// Parses the dot name, `TD` or `LR`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TD" | "TB" => Ok(RankDir::TopDown),
+            "LR" => Ok(RankDir::LeftRight),
+            _ => Err(format!("unknown rankdir `{s}`, expected `TD` or `LR`")),
+        }
+    }
+}
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Debug)]
 pub enum EdgeStyle {
     None,
     Line,
@@ -42,8 +57,28 @@ impl EdgeStyle {
         }
     }
 }
+impl FromStr for EdgeStyle {
+    type Err = String;
+
+    /// Parses the dot name, e.g. `ortho`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            EdgeStyle::None,
+            EdgeStyle::Line,
+            EdgeStyle::Polyline,
+            EdgeStyle::Curved,
+            EdgeStyle::Ortho,
+            EdgeStyle::Spline,
+        ]
+        .into_iter()
+        .find(|style| style.as_dot() == s)
+        .ok_or_else(|| {
+            format!("unknown edge style `{s}`, expected one of none, line, polyline, curved, ortho, spline")
+        })
+    }
+}
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Style {
     pub schedule_rankdir: RankDir,
     pub edge_style: EdgeStyle,
@@ -60,6 +95,11 @@ pub struct Style {
     pub ambiguity_color: String,
     pub ambiguity_bgcolor: String,
 
+    /// Color of highlighted systems, sets and dependencies, e.g. dependency paths
+    pub color_highlight: String,
+    /// Color of run condition nodes and the border of sets with run conditions
+    pub color_condition: String,
+
     pub penwidth_edge: f32,
 }
 // colors are from https://iamkate.com/data/12-bit-rainbow/, without the #cc6666
@@ -89,6 +129,8 @@ impl Style {
             multiple_set_edge_color: "blue".into(),
             ambiguity_color: "#c93526".into(),
             ambiguity_bgcolor: "#d3d3d3".into(),
+            color_highlight: "#e36209".into(),
+            color_condition: "#6f42c1".into(),
             penwidth_edge: 2.0,
         }
     }
@@ -117,6 +159,8 @@ impl Style {
             ],
             ambiguity_color: "#c93526".into(),
             ambiguity_bgcolor: "#c5daeb".into(),
+            color_highlight: "#faa61a".into(),
+            color_condition: "#b9a5f0".into(),
             multiple_set_edge_color: "blue".into(),
             penwidth_edge: 2.0,
         }
@@ -146,6 +190,8 @@ impl Style {
             ],
             ambiguity_color: "#c93526".into(),
             ambiguity_bgcolor: "#c6e6ff".into(),
+            color_highlight: "#ffa657".into(),
+            color_condition: "#d2a8ff".into(),
             multiple_set_edge_color: "blue".into(),
             penwidth_edge: 2.0,
         }
@@ -156,9 +202,62 @@ impl Default for Style {
         Style::dark_github()
     }
 }
+impl FromStr for Style {
+    type Err = String;
+
+    /// Parses the name of a preset: `light`, `dark_github` or `dark_discord`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Style::light()),
+            "dark_github" => Ok(Style::dark_github()),
+            "dark_discord" => Ok(Style::dark_discord()),
+            _ => Err(format!(
+                "unknown style `{s}`, expected light, dark_github or dark_discord"
+            )),
+        }
+    }
+}
+
+/// Which dependencies [`Focus`] follows
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    /// Systems that run before the focused systems
+    Upstream,
+    /// Systems that run after the focused systems
+    Downstream,
+    #[default]
+    Both,
+}
+impl FromStr for FocusDirection {
+    type Err = String;
+
+    /// Parses `upstream`, `downstream` or `both`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "upstream" => Ok(FocusDirection::Upstream),
+            "downstream" => Ok(FocusDirection::Downstream),
+            "both" => Ok(FocusDirection::Both),
+            _ => Err(format!(
+                "unknown focus direction `{s}`, expected upstream, downstream or both"
+            )),
+        }
+    }
+}
+
+/// Centres the graph on some systems, only including the systems within `depth` dependency hops of them.
+///
+/// Orderings of a set apply to all systems in it, so a system that runs after a set is one hop downstream of every system in the set.
+#[derive(Clone, Debug)]
+pub struct Focus {
+    /// The systems to centre on, use [`Filter::in_set`] to centre on the systems of a set
+    pub filter: Filter,
+    pub depth: usize,
+    pub direction: FocusDirection,
+}
 
 type IncludeAmbiguityFn = dyn Fn(&ScheduleSystem, &ScheduleSystem, &[ComponentId], &World) -> bool;
 
+#[derive(Clone, Debug)]
 pub struct NodeStyle {
     pub bg_color: String,
     pub text_color: String,
@@ -178,8 +277,18 @@ pub struct Settings {
 
     /// When set to `Some`, will only include systems matching the predicate, and their ancestor sets
     pub include_system: Option<SystemMapperFn<bool>>,
+    /// Like `include_system`, but composable and able to match on sets. Both have to match for a system to be included.
+    /// Use [`Settings::include`] and [`Settings::exclude`] to add rules.
+    /// Its patterns are globs only, see [`Filter`].
+    pub filter: Option<Filter>,
+    /// When set, only includes systems around the focused systems, and their ancestor sets
+    pub focus: Option<Focus>,
     pub collapse_single_system_sets: bool,
     pub remove_transitive_edges: bool,
+    /// Lists the components and resources each system reads and writes inside its node
+    pub show_access: bool,
+    /// Draws the run conditions of systems and sets as nodes attached to them. Off by default
+    pub show_conditions: bool,
 
     pub ambiguity_enable: bool,
     pub ambiguity_enable_on_world: bool,
@@ -191,6 +300,31 @@ pub struct Settings {
 }
 
 impl Settings {
+    /// Only include systems matching `filter`, in addition to the previous rules
+    pub fn include(mut self, filter: Filter) -> Self {
+        self.filter = Some(match self.filter {
+            Some(previous) => previous.and(filter),
+            None => filter,
+        });
+        self
+    }
+    /// Exclude systems matching `filter`, in addition to the previous rules
+    ///
+    /// Only systems are excluded, not set nodes. A set whose systems are all excluded is still drawn
+    /// if it is a top-level set or ordered against an included system.
+    pub fn exclude(self, filter: Filter) -> Self {
+        self.include(!filter)
+    }
+    /// Only include the systems within `depth` dependency hops of the systems matching `filter`, see [`Focus`]
+    pub fn focus_on(mut self, filter: Filter, depth: usize, direction: FocusDirection) -> Self {
+        self.focus = Some(Focus {
+            filter,
+            depth,
+            direction,
+        });
+        self
+    }
+
     /// Set the `include_system` predicate to match only systems for which their names matches `filter`
     pub fn filter_name(mut self, filter: impl Fn(&str) -> bool + 'static) -> Self {
         self.include_system = Some(Box::new(move |system| {
@@ -297,8 +431,12 @@ impl Default for Settings {
             system_style: Box::new(system_to_style),
 
             include_system: None,
+            filter: None,
+            focus: None,
             collapse_single_system_sets: false,
             remove_transitive_edges: true,
+            show_access: false,
+            show_conditions: false,
 
             ambiguity_enable: false,
             ambiguity_enable_on_world: false,
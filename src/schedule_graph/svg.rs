@@ -0,0 +1,438 @@
+use std::fmt::Write;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::hash_map::HashMap;
+
+use super::{
+    model::{ScheduleModel, SetKind},
+    settings::RankDir,
+    Settings,
+};
+use crate::{
+    dot::html_escape,
+    layout::{LayoutDirection, LayoutGraph, LayoutSpacing, Rect},
+};
+
+const FONT_SIZE: f32 = 14.0;
+const NODE_HEIGHT: f32 = 28.0;
+const NODE_PADDING: f32 = 10.0;
+const LABEL_HEIGHT: f32 = 22.0;
+const ARROW_SIZE: f32 = 8.0;
+
+/// Lays out the schedule model and renders it as SVG, without requiring graphviz.
+///
+/// This produces the same sets, systems and edges as the dot output, but uses a simpler layout.
+pub fn schedule_model_svg(model: &ScheduleModel, settings: &Settings) -> String {
+    let mut context = SvgContext {
+        model,
+        graph: LayoutGraph::default(),
+        items: Vec::new(),
+        parents: Vec::new(),
+        item_of_node: HashMap::default(),
+    };
+
+    for &set_id in &model.sets_freestanding {
+        context.add_set(set_id, None);
+    }
+    for &set_id in model.sets_in_multiple_sets(None) {
+        context.add_set(set_id, None);
+    }
+    for &system_id in model
+        .systems_freestanding
+        .iter()
+        .chain(model.systems_in_multiple_sets(None))
+    {
+        context.add_system(system_id, None);
+    }
+    context.add_system_type_sets();
+
+    let edges: Vec<_> = model
+        .drawn_dependencies()
+        .filter_map(|edge| Some((context.resolve(edge.from)?, context.resolve(edge.to)?, edge)))
+        .collect();
+    for &(from, to, _) in &edges {
+        context.graph.add_edge(from, to);
+    }
+
+    let direction = match settings.style.schedule_rankdir {
+        RankDir::LeftRight => LayoutDirection::LeftRight,
+        RankDir::TopDown => LayoutDirection::TopDown,
+    };
+    let spacing = LayoutSpacing::default();
+    let rects = context.graph.layout(direction, &spacing);
+
+    let (width, height) = rects.iter().fold((0.0f32, 0.0f32), |(w, h), rect| {
+        (w.max(rect.x + rect.width), h.max(rect.y + rect.height))
+    });
+    let (width, height) = (width + spacing.margin, height + spacing.margin);
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="{}" font-size="{FONT_SIZE}">"#,
+        html_escape(&settings.style.fontname),
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        html_escape(&settings.style.color_background)
+    );
+
+    // clusters are added before their children, so nested clusters are drawn on top
+    for (i, (item, rect)) in context.items.iter().zip(&rects).enumerate() {
+        if let SvgItem::Cluster { label } = item {
+            write_cluster(&mut svg, &context.item_attrs(i), rect, label, settings);
+        }
+    }
+
+    let mut color_edge = settings.style.color_edge.iter().cycle();
+    for &(from, to, edge) in &edges {
+        let tooltip = format!(
+            "{} → {}",
+            model.node_name(edge.from),
+            model.node_name(edge.to)
+        );
+        let color = color_edge.next().map_or("black", String::as_str);
+        write_edge(
+            &mut svg,
+            &format!(r#" class="edge" data-from="{from}" data-to="{to}""#),
+            &rects[from],
+            &rects[to],
+            direction,
+            color,
+            settings.style.penwidth_edge,
+            &tooltip,
+        );
+    }
+
+    for system in &model.systems {
+        if system.parents.len() < 2 {
+            continue;
+        }
+        let Some(from) = context.resolve(system.id) else {
+            continue;
+        };
+        for &parent in &system.parents {
+            if let Some(parent) = context.item_of_node.get(&parent) {
+                let rect = &rects[*parent];
+                write_line(
+                    &mut svg,
+                    &format!(r#" class="membership" data-from="{from}" data-to="{parent}""#),
+                    rects[from].center(),
+                    (rect.x + rect.width / 2.0, rect.y),
+                    &settings.style.multiple_set_edge_color,
+                    None,
+                );
+            }
+        }
+    }
+
+    for (i, (item, rect)) in context.items.iter().zip(&rects).enumerate() {
+        let attrs = context.item_attrs(i);
+        match item {
+            SvgItem::Cluster { .. } => {}
+            SvgItem::System { id } => {
+                let system = model.system(*id).unwrap();
+                let style = &system.style;
+                write_node(
+                    &mut svg,
+                    &attrs,
+                    rect,
+                    &system.name,
+                    &system.full_name,
+                    [&style.bg_color, &style.border_color, &style.text_color],
+                    &style.border_width,
+                );
+            }
+            SvgItem::Node { label } => {
+                let style = &settings.style;
+                write_node(
+                    &mut svg,
+                    &attrs,
+                    rect,
+                    label,
+                    label,
+                    [
+                        &style.color_set,
+                        &style.color_set_border,
+                        &style.color_set_label,
+                    ],
+                    "1",
+                );
+            }
+        }
+    }
+
+    if settings.ambiguity_enable {
+        for ambiguity in &model.ambiguities {
+            let (Some(a), Some(b)) = (
+                context.resolve(ambiguity.system_a),
+                context.resolve(ambiguity.system_b),
+            ) else {
+                continue;
+            };
+            let label = if ambiguity.conflicts.is_empty() {
+                "World".to_owned()
+            } else {
+                ambiguity
+                    .conflicts
+                    .iter()
+                    .map(|name| disqualified::ShortName(name).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            write_line(
+                &mut svg,
+                &format!(r#" class="ambiguity" data-from="{a}" data-to="{b}""#),
+                rects[a].center(),
+                rects[b].center(),
+                &settings.style.ambiguity_color,
+                Some(&label),
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+enum SvgItem {
+    Cluster {
+        label: String,
+    },
+    System {
+        id: NodeId,
+    },
+    /// Collapsed sets and system type sets
+    Node {
+        label: String,
+    },
+}
+
+struct SvgContext<'a> {
+    model: &'a ScheduleModel,
+
+    graph: LayoutGraph,
+    items: Vec<SvgItem>,
+    /// Enclosing cluster of every item
+    parents: Vec<Option<usize>>,
+    item_of_node: HashMap<NodeId, usize>,
+}
+
+impl SvgContext<'_> {
+    fn add_set(&mut self, set_id: NodeId, parent: Option<usize>) {
+        let name = self.model.set(set_id).unwrap().name.clone();
+
+        if self.model.collapsed_sets.contains(&set_id) {
+            self.add_node(set_id, parent, SvgItem::Node { label: name });
+            return;
+        }
+
+        let item = self
+            .graph
+            .add_cluster(parent, text_width(&name), LABEL_HEIGHT);
+        self.items.push(SvgItem::Cluster { label: name });
+        self.parents.push(parent);
+        self.item_of_node.insert(set_id, item);
+
+        let model = self.model;
+        for &nested_set_id in model
+            .sets_in_single_set(set_id)
+            .iter()
+            .chain(model.sets_in_multiple_sets(Some(set_id)))
+        {
+            self.add_set(nested_set_id, Some(item));
+        }
+        for &system_id in model
+            .systems_in_single_set(set_id)
+            .iter()
+            .chain(model.systems_in_multiple_sets(Some(set_id)))
+        {
+            self.add_system(system_id, Some(item));
+        }
+    }
+
+    fn add_system(&mut self, system_id: NodeId, parent: Option<usize>) {
+        // systems in collapsed sets are drawn as their set
+        if self.model.collapsed_set_children.contains_key(&system_id) {
+            return;
+        }
+        self.add_node(system_id, parent, SvgItem::System { id: system_id });
+    }
+
+    /// Add nodes for system type sets that don't resolve to an included system
+    fn add_system_type_sets(&mut self) {
+        let model = self.model;
+        for set in &model.sets {
+            let label = match set.kind {
+                SetKind::Regular => continue,
+                SetKind::SystemType {
+                    system: Some(system),
+                } if model.contains(system) => continue,
+                SetKind::SystemType { system: Some(_) } => set.name.clone(),
+                SetKind::ApplyDeferred => "ApplyDeferred".to_owned(),
+                SetKind::SystemType { system: None } => format!("<missing> {}", set.name),
+            };
+            self.add_node(set.id, None, SvgItem::Node { label });
+        }
+    }
+
+    fn add_node(&mut self, id: NodeId, parent: Option<usize>, item: SvgItem) {
+        let label = match &item {
+            SvgItem::System { id } => &self.model.system(*id).unwrap().name,
+            SvgItem::Node { label } | SvgItem::Cluster { label } => label,
+        };
+        let index =
+            self.graph
+                .add_node(parent, text_width(label) + 2.0 * NODE_PADDING, NODE_HEIGHT);
+        self.items.push(item);
+        self.parents.push(parent);
+        self.item_of_node.insert(id, index);
+    }
+
+    /// Attributes identifying the item, used by the html viewer
+    fn item_attrs(&self, index: usize) -> String {
+        let class = match self.items[index] {
+            SvgItem::Cluster { .. } => "cluster",
+            SvgItem::System { .. } | SvgItem::Node { .. } => "node",
+        };
+        let mut attrs = format!(r#" class="{class}" data-item="{index}""#);
+        if let Some(parent) = self.parents[index] {
+            let _ = write!(attrs, r#" data-parent="{parent}""#);
+        }
+        attrs
+    }
+
+    fn resolve(&self, node_id: NodeId) -> Option<usize> {
+        let node_id = match self.model.set(node_id).map(|set| set.kind) {
+            Some(SetKind::SystemType {
+                system: Some(system),
+            }) if self.model.contains(system) => system,
+            _ => node_id,
+        };
+        let node_id = self
+            .model
+            .collapsed_set_children
+            .get(&node_id)
+            .copied()
+            .unwrap_or(node_id);
+        self.item_of_node.get(&node_id).copied()
+    }
+}
+
+fn text_width(text: &str) -> f32 {
+    // rough average glyph width of proportional fonts
+    text.chars().count() as f32 * FONT_SIZE * 0.6
+}
+
+fn write_cluster(svg: &mut String, attrs: &str, rect: &Rect, label: &str, settings: &Settings) {
+    let style = &settings.style;
+    let _ = writeln!(
+        svg,
+        r#"<g{attrs}><title>{label}</title><rect x="{}" y="{}" width="{}" height="{}" rx="8" fill="{}" stroke="{}" stroke-width="2"/><text x="{}" y="{}" text-anchor="middle" dominant-baseline="central" fill="{}">{label}</text></g>"#,
+        rect.x,
+        rect.y,
+        rect.width,
+        rect.height,
+        html_escape(&style.color_set),
+        html_escape(&style.color_set_border),
+        rect.x + rect.width / 2.0,
+        rect.y + LABEL_HEIGHT / 2.0 + 6.0,
+        html_escape(&style.color_set_label),
+        label = html_escape(label),
+    );
+}
+
+/// Draws a box with the given fill, stroke and text color
+fn write_node(
+    svg: &mut String,
+    attrs: &str,
+    rect: &Rect,
+    label: &str,
+    tooltip: &str,
+    [fill, stroke, text]: [&str; 3],
+    stroke_width: &str,
+) {
+    let (cx, cy) = rect.center();
+    let _ = writeln!(
+        svg,
+        r#"<g{attrs}><title>{}</title><rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="{}"/><text x="{cx}" y="{cy}" text-anchor="middle" dominant-baseline="central" fill="{}">{}</text></g>"#,
+        html_escape(tooltip),
+        rect.x,
+        rect.y,
+        rect.width,
+        rect.height,
+        html_escape(fill),
+        html_escape(stroke),
+        html_escape(stroke_width),
+        html_escape(text),
+        html_escape(label),
+    );
+}
+
+#[expect(clippy::too_many_arguments)]
+fn write_edge(
+    svg: &mut String,
+    attrs: &str,
+    from: &Rect,
+    to: &Rect,
+    direction: LayoutDirection,
+    color: &str,
+    width: f32,
+    tooltip: &str,
+) {
+    // start and end point, and the unit vector the edge leaves and enters with
+    let ((sx, sy), (ex, ey), (dx, dy)) = match direction {
+        LayoutDirection::LeftRight => (
+            (from.x + from.width, from.y + from.height / 2.0),
+            (to.x, to.y + to.height / 2.0),
+            (1.0, 0.0),
+        ),
+        LayoutDirection::TopDown => (
+            (from.x + from.width / 2.0, from.y + from.height),
+            (to.x + to.width / 2.0, to.y),
+            (0.0, 1.0),
+        ),
+    };
+    let bend = ((ex - sx) * dx + (ey - sy) * dy).abs().max(40.0) / 2.0;
+    let (tx, ty) = (ex - dx * ARROW_SIZE, ey - dy * ARROW_SIZE);
+    let color = html_escape(color);
+    let _ = writeln!(
+        svg,
+        r#"<g{attrs}><title>{}</title><path d="M{sx},{sy} C{},{} {},{} {tx},{ty}" fill="none" stroke="{color}" stroke-width="{width}"/><polygon points="{ex},{ey} {},{} {},{}" fill="{color}"/></g>"#,
+        html_escape(tooltip),
+        sx + dx * bend,
+        sy + dy * bend,
+        tx - dx * bend,
+        ty - dy * bend,
+        tx - dy * ARROW_SIZE / 2.0,
+        ty - dx * ARROW_SIZE / 2.0,
+        tx + dy * ARROW_SIZE / 2.0,
+        ty + dx * ARROW_SIZE / 2.0,
+    );
+}
+
+fn write_line(
+    svg: &mut String,
+    attrs: &str,
+    (x1, y1): (f32, f32),
+    (x2, y2): (f32, f32),
+    color: &str,
+    label: Option<&str>,
+) {
+    let color = html_escape(color);
+    let _ = write!(
+        svg,
+        r#"<g{attrs}><line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{color}" stroke-dasharray="4 3"/>"#,
+    );
+    if let Some(label) = label {
+        let _ = write!(
+            svg,
+            r#"<text x="{}" y="{}" text-anchor="middle" fill="{color}">{}</text>"#,
+            (x1 + x2) / 2.0,
+            (y1 + y2) / 2.0,
+            html_escape(label),
+        );
+    }
+    svg.push_str("</g>\n");
+}
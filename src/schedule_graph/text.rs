@@ -0,0 +1,275 @@
+use std::fmt::Write;
+
+use bevy_ecs::schedule::NodeId;
+use bevy_platform::collections::hash_map::HashMap;
+
+use super::{
+    model::{ConditionNode, ScheduleModel, SetKind},
+    Settings,
+};
+
+/// Formats the schedule model as plain text, for terminals and CI logs.
+///
+/// Sets are printed as `[Set]` with their content indented below them,
+/// and every set and system lists the nodes it runs `before` and `after`, as well as its run conditions.
+///
+/// ```text
+/// Update
+///   [Physics]
+///       after: [Input]
+///     move_player
+///         before: detect_collisions
+///         if: is_playing
+/// ```
+pub fn schedule_model_text(model: &ScheduleModel, settings: &Settings) -> String {
+    let mut context = TextContext {
+        model,
+        before: HashMap::default(),
+        after: HashMap::default(),
+        buffer: String::new(),
+    };
+
+    for edge in model.drawn_dependencies() {
+        let (from, to) = (context.resolve(edge.from), context.resolve(edge.to));
+        if from == to {
+            continue;
+        }
+        let before = context.before.entry(from).or_default();
+        if !before.contains(&to) {
+            before.push(to);
+        }
+        let after = context.after.entry(to).or_default();
+        if !after.contains(&from) {
+            after.push(from);
+        }
+    }
+
+    let _ = writeln!(context.buffer, "{}", model.label);
+    for &set_id in model
+        .sets_freestanding
+        .iter()
+        .chain(model.sets_in_multiple_sets(None))
+    {
+        context.add_set(set_id, 1);
+    }
+    for &system_id in model
+        .systems_freestanding
+        .iter()
+        .chain(model.systems_in_multiple_sets(None))
+    {
+        context.add_system(system_id, 1);
+    }
+
+    if settings.ambiguity_enable && !model.ambiguities.is_empty() {
+        let _ = writeln!(context.buffer, "\nAmbiguities");
+        for ambiguity in &model.ambiguities {
+            let conflicts = if ambiguity.conflicts.is_empty() {
+                "World".to_owned()
+            } else {
+                ambiguity
+                    .conflicts
+                    .iter()
+                    .map(|name| disqualified::ShortName(name).to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let _ = writeln!(
+                context.buffer,
+                "  {} <-> {}: {conflicts}",
+                model.node_name(ambiguity.system_a),
+                model.node_name(ambiguity.system_b),
+            );
+        }
+    }
+
+    context.buffer
+}
+
+struct TextContext<'a> {
+    model: &'a ScheduleModel,
+
+    before: HashMap<NodeId, Vec<NodeId>>,
+    after: HashMap<NodeId, Vec<NodeId>>,
+    buffer: String,
+}
+
+impl TextContext<'_> {
+    fn add_set(&mut self, set_id: NodeId, depth: usize) {
+        let model = self.model;
+        let set = model.set(set_id).unwrap();
+        let mut line = format!("[{}]", set.name);
+
+        if model.collapsed_sets.contains(&set_id) {
+            let count = model
+                .collapsed_set_children
+                .values()
+                .filter(|&&set| set == set_id)
+                .count();
+            let _ = write!(line, " ({count} systems, collapsed)");
+        }
+        self.write_parents(&mut line, &set.parents);
+        self.write_node(set_id, &line, &set.conditions, depth);
+
+        if model.collapsed_sets.contains(&set_id) {
+            // the systems aren't listed, so their run conditions are listed with the set
+            let indent = "  ".repeat(depth);
+            for system in &model.systems {
+                if model.collapsed_set_children.get(&system.id) == Some(&set_id) {
+                    for condition in &system.conditions {
+                        let prefix = format!("{indent}    if ({}): ", system.name);
+                        self.write_condition(&prefix, condition);
+                    }
+                }
+            }
+            return;
+        }
+
+        for &nested_set_id in model
+            .sets_in_single_set(set_id)
+            .iter()
+            .chain(model.sets_in_multiple_sets(Some(set_id)))
+        {
+            self.add_set(nested_set_id, depth + 1);
+        }
+        for &system_id in model
+            .systems_in_single_set(set_id)
+            .iter()
+            .chain(model.systems_in_multiple_sets(Some(set_id)))
+        {
+            self.add_system(system_id, depth + 1);
+        }
+    }
+
+    fn add_system(&mut self, system_id: NodeId, depth: usize) {
+        // systems in collapsed sets are listed as their set
+        if self.model.collapsed_set_children.contains_key(&system_id) {
+            return;
+        }
+
+        let system = self.model.system(system_id).unwrap();
+        let mut line = system.name.clone();
+        self.write_parents(&mut line, &system.parents);
+        self.write_node(system_id, &line, &system.conditions, depth);
+    }
+
+    /// Mentions all parents of nodes in multiple sets, since they are only listed under one
+    fn write_parents(&self, line: &mut String, parents: &[NodeId]) {
+        if parents.len() > 1 {
+            let parents: Vec<_> = parents
+                .iter()
+                .map(|&parent| self.model.node_name(parent))
+                .collect();
+            let _ = write!(line, " (in multiple sets: {})", parents.join(", "));
+        }
+    }
+
+    fn write_node(
+        &mut self,
+        node_id: NodeId,
+        line: &str,
+        conditions: &[ConditionNode],
+        depth: usize,
+    ) {
+        let indent = "  ".repeat(depth);
+        let _ = writeln!(self.buffer, "{indent}{line}");
+
+        for (label, neighbours) in [("before", &self.before), ("after", &self.after)] {
+            let Some(neighbours) = neighbours.get(&node_id) else {
+                continue;
+            };
+            let names: Vec<_> = neighbours.iter().map(|&id| self.node_ref(id)).collect();
+            let line = format!("{indent}    {label}: {}\n", names.join(", "));
+            self.buffer.push_str(&line);
+        }
+        for condition in conditions {
+            self.write_condition(&format!("{indent}    if: "), condition);
+        }
+    }
+
+    fn write_condition(&mut self, prefix: &str, condition: &ConditionNode) {
+        let _ = write!(
+            self.buffer,
+            "{prefix}{}",
+            disqualified::ShortName(&condition.name)
+        );
+        let access = &condition.access;
+        let reads: Vec<_> = (access.resources_read.iter())
+            .chain(&access.components_read)
+            .map(|name| disqualified::ShortName(name).to_string())
+            .collect();
+        if !reads.is_empty() {
+            let _ = write!(self.buffer, " (reads {})", reads.join(", "));
+        }
+        self.buffer.push('\n');
+    }
+
+    /// Name used when listing a node as a neighbour
+    fn node_ref(&self, node_id: NodeId) -> String {
+        match self.model.set(node_id) {
+            Some(set) => match set.kind {
+                SetKind::Regular => format!("[{}]", set.name),
+                SetKind::ApplyDeferred => "ApplyDeferred".to_owned(),
+                SetKind::SystemType { system: None } => format!("<missing> {}", set.name),
+                SetKind::SystemType { system: Some(_) } => set.name.clone(),
+            },
+            None => self.model.node_name(node_id).to_owned(),
+        }
+    }
+
+    fn resolve(&self, node_id: NodeId) -> NodeId {
+        let node_id = match self.model.set(node_id).map(|set| set.kind) {
+            Some(SetKind::SystemType {
+                system: Some(system),
+            }) if self.model.contains(system) => system,
+            _ => node_id,
+        };
+        self.model
+            .collapsed_set_children
+            .get(&node_id)
+            .copied()
+            .unwrap_or(node_id)
+    }
+}
+
+#[test]
+fn text_output() {
+    use bevy_ecs::prelude::*;
+
+    #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+    struct Physics;
+    #[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+    struct Input;
+
+    fn read_input() {}
+    fn move_player() {}
+    fn detect_collisions() {}
+    fn is_playing() -> bool {
+        true
+    }
+
+    let (schedule, world) = super::model::test_schedule((
+        read_input.in_set(Input).run_if(is_playing).before(Physics),
+        (move_player, detect_collisions).chain().in_set(Physics),
+    ));
+
+    let settings = Settings {
+        collapse_single_system_sets: true,
+        ..Settings::default()
+    };
+    let model = ScheduleModel::new(&schedule, &world, &settings);
+    assert_eq!(
+        schedule_model_text(&model, &settings),
+        "\
+DefaultSchedule
+  [Input] (1 systems, collapsed)
+      before: [Physics]
+      if (read_input): is_playing
+  [Physics]
+      after: [Input]
+    move_player
+        before: detect_collisions
+    detect_collisions
+        after: move_player
+"
+    );
+}